@@ -0,0 +1,271 @@
+use std::path::PathBuf;
+
+use common::Source;
+
+pub static USAGE: &str = "usage:
+  aoc run <day> [--part <1|2>] [--input <path|->] [--input-str <puzzle input>] [--format <text|json>]
+  aoc verify [--answers <path>] [--day <day>]
+  aoc gen <day> [--size <count>] [--seed <seed>]";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+  Run(RunArgs),
+  Verify(VerifyArgs),
+  Gen(GenArgs),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Format {
+  Text,
+  Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+  pub day: u8,
+  pub part: Option<u8>,
+  pub source: Option<Source>,
+  pub format: Format,
+}
+
+impl RunArgs {
+  /// Falls back to the day's own `input.txt` relative to the workspace root.
+  pub fn source(&self) -> Source {
+    match &self.source {
+      Some(source) => source.clone(),
+      None => Source::Path(PathBuf::from(format!("day{}/input.txt", self.day))),
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyArgs {
+  pub answers: PathBuf,
+  pub day: Option<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenArgs {
+  pub day: u8,
+  pub size: usize,
+  pub seed: u64,
+}
+
+fn parse_day(input: &str) -> Result<u8, String> {
+  match input.parse::<u8>() {
+    Ok(day) if (1..=25).contains(&day) => Ok(day),
+    _ => Err(format!("invalid day \"{}\"", input)),
+  }
+}
+
+fn parse_part(input: &str) -> Result<u8, String> {
+  match input {
+    "1" => Ok(1),
+    "2" => Ok(2),
+    _ => Err(format!("invalid part \"{}\"", input)),
+  }
+}
+
+fn parse_format(input: &str) -> Result<Format, String> {
+  match input {
+    "text" => Ok(Format::Text),
+    "json" => Ok(Format::Json),
+    _ => Err(format!("invalid format \"{}\"", input)),
+  }
+}
+
+fn parse_run(args: &[String]) -> Result<RunArgs, String> {
+  let mut iter = args.iter();
+  let day = parse_day(iter.next().ok_or("missing day")?)?;
+  let mut part = None;
+  let mut source = None;
+  let mut format = Format::Text;
+
+  while let Some(flag) = iter.next() {
+    let mut value = || iter
+      .next()
+      .ok_or(format!("missing value for {}", flag));
+
+    match flag.as_str() {
+      "--part" | "-p" => part = Some(parse_part(value()?)?),
+      "--input" | "-i" => source = Some(Source::from_arg(value()?)),
+      "--input-str" => source = Some(Source::Inline(value()?.clone())),
+      "--format" | "-f" => format = parse_format(value()?)?,
+      _ => return Err(format!("unknown argument \"{}\"", flag)),
+    }
+  }
+
+  Ok(RunArgs { day, part, source, format })
+}
+
+fn parse_verify(args: &[String]) -> Result<VerifyArgs, String> {
+  let mut iter = args.iter();
+  let mut answers = PathBuf::from("answers.toml");
+  let mut day = None;
+
+  while let Some(flag) = iter.next() {
+    let mut value = || iter
+      .next()
+      .ok_or(format!("missing value for {}", flag));
+
+    match flag.as_str() {
+      "--answers" | "-a" => answers = PathBuf::from(value()?),
+      "--day" | "-d" => day = Some(parse_day(value()?)?),
+      _ => return Err(format!("unknown argument \"{}\"", flag)),
+    }
+  }
+
+  Ok(VerifyArgs { answers, day })
+}
+
+fn parse_gen(args: &[String]) -> Result<GenArgs, String> {
+  let mut iter = args.iter();
+  let day = parse_day(iter.next().ok_or("missing day")?)?;
+  let mut size = 1000;
+  let mut seed = 0;
+
+  while let Some(flag) = iter.next() {
+    let mut value = || iter
+      .next()
+      .ok_or(format!("missing value for {}", flag));
+
+    match flag.as_str() {
+      "--size" | "-n" => size = value()?
+        .parse()
+        .map_err(|_| format!("invalid size for {}", flag))?,
+      "--seed" | "-s" => seed = value()?
+        .parse()
+        .map_err(|_| format!("invalid seed for {}", flag))?,
+      _ => return Err(format!("unknown argument \"{}\"", flag)),
+    }
+  }
+
+  Ok(GenArgs { day, size, seed })
+}
+
+pub fn parse(args: &[String]) -> Result<Command, String> {
+  match args.split_first() {
+    Some((command, rest)) if command == "run" => Ok(Command::Run(parse_run(rest)?)),
+    Some((command, rest)) if command == "verify" => Ok(Command::Verify(parse_verify(rest)?)),
+    Some((command, rest)) if command == "gen" => Ok(Command::Gen(parse_gen(rest)?)),
+    Some((command, _)) => Err(format!("unknown command \"{}\"", command)),
+    None => Err("missing command".to_string()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn to_args(input: &str) -> Vec<String> {
+    input.split_whitespace().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn parse_test_001() {
+    assert_eq!(
+      parse(&to_args("run 4 --part 2 --input path")),
+      Ok(Command::Run(RunArgs {
+        day: 4,
+        part: Some(2),
+        source: Some(Source::Path(PathBuf::from("path"))),
+        format: Format::Text,
+      }))
+    );
+  }
+
+  #[test]
+  fn parse_test_002() {
+    assert_eq!(
+      parse(&to_args("run 1")),
+      Ok(Command::Run(RunArgs { day: 1, part: None, source: None, format: Format::Text }))
+    );
+  }
+
+  #[test]
+  fn parse_test_004() {
+    assert_eq!(
+      parse(&to_args("run 1 --input -")),
+      Ok(Command::Run(RunArgs {
+        day: 1,
+        part: None,
+        source: Some(Source::Stdin),
+        format: Format::Text,
+      }))
+    );
+
+    assert_eq!(
+      parse(&[
+        "run".to_string(),
+        "1".to_string(),
+        "--input-str".to_string(),
+        "+1\n-2".to_string(),
+      ]),
+      Ok(Command::Run(RunArgs {
+        day: 1,
+        part: None,
+        source: Some(Source::Inline("+1\n-2".to_string())),
+        format: Format::Text,
+      }))
+    );
+  }
+
+  #[test]
+  fn parse_test_005() {
+    match parse(&to_args("run 2 --format json")) {
+      Ok(Command::Run(args)) => assert_eq!(args.format, Format::Json),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    assert!(parse(&to_args("run 2 --format yaml")).is_err());
+  }
+
+  #[test]
+  fn parse_test_003() {
+    assert!(parse(&to_args("")).is_err());
+    assert!(parse(&to_args("walk 1")).is_err());
+    assert!(parse(&to_args("run")).is_err());
+    assert!(parse(&to_args("run 0")).is_err());
+    assert!(parse(&to_args("run 1 --part 3")).is_err());
+    assert!(parse(&to_args("run 1 --part")).is_err());
+    assert!(parse(&to_args("run 1 --verbose")).is_err());
+  }
+
+  #[test]
+  fn parse_verify_test() {
+    assert_eq!(
+      parse(&to_args("verify")),
+      Ok(Command::Verify(VerifyArgs { answers: PathBuf::from("answers.toml"), day: None }))
+    );
+
+    assert_eq!(
+      parse(&to_args("verify --answers other.toml --day 4")),
+      Ok(Command::Verify(VerifyArgs { answers: PathBuf::from("other.toml"), day: Some(4) }))
+    );
+
+    assert!(parse(&to_args("verify --day")).is_err());
+    assert!(parse(&to_args("verify 4")).is_err());
+  }
+
+  #[test]
+  fn parse_gen_test() {
+    assert_eq!(
+      parse(&to_args("gen 3")),
+      Ok(Command::Gen(GenArgs { day: 3, size: 1000, seed: 0 }))
+    );
+
+    assert_eq!(
+      parse(&to_args("gen 3 --size 100000 --seed 42")),
+      Ok(Command::Gen(GenArgs { day: 3, size: 100000, seed: 42 }))
+    );
+
+    assert!(parse(&to_args("gen")).is_err());
+    assert!(parse(&to_args("gen 3 --size many")).is_err());
+  }
+
+  #[test]
+  fn source_test() {
+    let args = RunArgs { day: 3, part: None, source: None, format: Format::Text };
+    assert_eq!(args.source(), Source::Path(PathBuf::from("day3/input.txt")));
+  }
+}
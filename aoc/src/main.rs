@@ -0,0 +1,74 @@
+mod args;
+mod report;
+mod verify;
+
+use std::env;
+use std::path::Path;
+use std::process;
+
+use args::{Command, Format, GenArgs, RunArgs, VerifyArgs};
+use report::solve_day;
+use verify::{read_answers, verify, Verdict};
+
+fn run(args: &RunArgs) -> Result<(), String> {
+  for report in solve_day(args.day, &args.source(), args.part)? {
+    match args.format {
+      Format::Text => println!("{}", report.to_text()),
+      Format::Json => println!("{}", report.to_json()),
+    }
+  }
+
+  Ok(())
+}
+
+fn run_verify(args: &VerifyArgs) -> Result<(), String> {
+  let answers = read_answers(&args.answers)?;
+  let base = args.answers.parent().unwrap_or_else(|| Path::new(""));
+
+  let verdicts: Vec<Verdict> = answers.answer
+    .iter()
+    .filter(|expected| args.day.is_none() || args.day == Some(expected.day))
+    .flat_map(|expected| verify(expected, base))
+    .collect();
+
+  for verdict in verdicts.iter() {
+    println!("{}", verdict);
+  }
+
+  match verdicts.iter().filter(|v| !v.is_pass()).count() {
+    0 => Ok(()),
+    failed => Err(format!("{} of {} answers did not verify", failed, verdicts.len())),
+  }
+}
+
+fn run_gen(args: &GenArgs) -> Result<(), String> {
+  let input = match args.day {
+    1 => day1::generate(args.size, args.seed),
+    2 => day2::generate(args.size, args.seed),
+    3 => day3::generate(args.size, args.seed),
+    4 => day4::generate(args.size, args.seed),
+    5 => day5::generate(args.size, args.seed),
+    6 => day6::generate(args.size, args.seed),
+    7 => day7::generate(args.size, args.seed),
+    day => return Err(format!("day {} has no generator", day)),
+  };
+
+  println!("{}", input);
+  Ok(())
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let result = args::parse(&args)
+    .map_err(|e| format!("{}\n{}", e, args::USAGE))
+    .and_then(|command| match command {
+      Command::Run(run_args) => run(&run_args),
+      Command::Verify(verify_args) => run_verify(&verify_args),
+      Command::Gen(gen_args) => run_gen(&gen_args),
+    });
+
+  if let Err(e) = result {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use common::{Solution, Source};
+
+/// Outcome of solving one part of a day, along with how long it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+  pub day: u8,
+  pub part: u8,
+  pub answer: String,
+  pub parse_ms: f64,
+  pub solve_ms: f64,
+}
+
+impl Report {
+  pub fn to_text(&self) -> String {
+    format!("Day #{}, part #{} {}", self.day, self.part, self.answer)
+  }
+
+  pub fn to_json(&self) -> String {
+    json!({
+      "day": self.day,
+      "part": self.part,
+      "answer": self.answer,
+      "parse_ms": self.parse_ms,
+      "solve_ms": self.solve_ms,
+    })
+    .to_string()
+  }
+}
+
+fn as_ms(duration: Duration) -> f64 {
+  duration.as_secs_f64() * 1000.0
+}
+
+/// Parses the input once and solves the requested part, or both of them.
+pub fn solve<S: Solution>(source: &Source, part: Option<u8>) -> common::Result<Vec<Report>> {
+  let start = Instant::now();
+  let input = S::read(source)?;
+  let parse_ms = as_ms(start.elapsed());
+
+  let mut reports = vec![];
+
+  for p in 1..=2 {
+    if part.is_some() && part != Some(p) {
+      continue;
+    }
+
+    let start = Instant::now();
+    let answer = match p {
+      1 => S::part1(&input)?,
+      _ => S::part2(&input)?,
+    };
+    let solve_ms = as_ms(start.elapsed());
+
+    reports.push(Report { day: S::DAY, part: p, answer: answer.to_string(), parse_ms, solve_ms });
+  }
+
+  Ok(reports)
+}
+
+pub fn solve_day(day: u8, source: &Source, part: Option<u8>) -> Result<Vec<Report>, String> {
+  match day {
+    1 => solve::<day1::Day1>(source, part),
+    2 => solve::<day2::Day2>(source, part),
+    3 => solve::<day3::Day3>(source, part),
+    4 => solve::<day4::Day4>(source, part),
+    5 => solve::<day5::Day5>(source, part),
+    6 => solve::<day6::Day6>(source, part),
+    7 => solve::<day7::Day7>(source, part),
+    day => return Err(format!("day {} is not solved yet", day)),
+  }
+  .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_text_test() {
+    let report = Report { day: 4, part: 2, answer: "42".to_string(), parse_ms: 1.5, solve_ms: 0.25 };
+    assert_eq!(report.to_text(), "Day #4, part #2 42".to_string());
+  }
+
+  #[test]
+  fn to_json_test() {
+    let report = Report { day: 4, part: 2, answer: "42".to_string(), parse_ms: 1.5, solve_ms: 0.25 };
+    assert_eq!(
+      report.to_json(),
+      r#"{"day":4,"part":2,"answer":"42","parse_ms":1.5,"solve_ms":0.25}"#.to_string()
+    );
+  }
+
+  #[test]
+  fn solve_test() {
+    let source = Source::Inline("+1\n-2\n+3\n+1\n".to_string());
+    let reports = solve::<day1::Day1>(&source, None).unwrap();
+
+    assert_eq!(
+      reports.iter().map(|r| (r.part, r.answer.clone())).collect::<Vec<_>>(),
+      vec![(1, "3".to_string()), (2, "2".to_string())]
+    );
+    assert_eq!(solve::<day1::Day1>(&source, Some(2)).unwrap().len(), 1);
+    assert!(solve_day(8, &source, None).is_err());
+  }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use common::Source;
+
+use crate::report::solve_day;
+
+/// Expected answers of a single day for one particular input file.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Expected {
+  pub day: u8,
+  pub input: PathBuf,
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct Answers {
+  #[serde(default)]
+  pub answer: Vec<Expected>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+  Pass,
+  Fail(String),
+  Regression { expected: String, actual: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Verdict {
+  pub day: u8,
+  pub part: u8,
+  pub input: PathBuf,
+  pub outcome: Outcome,
+}
+
+impl Verdict {
+  pub fn is_pass(&self) -> bool {
+    self.outcome == Outcome::Pass
+  }
+}
+
+impl fmt::Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let prefix = format!("day #{}, part #{} ({})", self.day, self.part, self.input.display());
+
+    match &self.outcome {
+      Outcome::Pass => write!(f, "pass       {}", prefix),
+      Outcome::Fail(e) => write!(f, "fail       {}: {}", prefix, e),
+      Outcome::Regression { expected, actual } => write!(
+        f,
+        "regression {}: expected {}, got {}",
+        prefix, expected, actual
+      ),
+    }
+  }
+}
+
+pub fn parse_answers(input: &str) -> Result<Answers, String> {
+  toml::from_str(input).map_err(|e| e.to_string())
+}
+
+pub fn read_answers(filepath: &Path) -> Result<Answers, String> {
+  let contents = fs::read_to_string(filepath)
+    .map_err(|e| format!("could not read {}: {}", filepath.display(), e))?;
+
+  parse_answers(&contents).map_err(|e| format!("could not parse {}: {}", filepath.display(), e))
+}
+
+/// Solves the expected input once and compares every recorded part.
+/// `base` is the directory the input path is relative to.
+pub fn verify(expected: &Expected, base: &Path) -> Vec<Verdict> {
+  let source = Source::Path(base.join(&expected.input));
+  let solved = solve_day(expected.day, &source, None);
+
+  [(1, &expected.part1), (2, &expected.part2)]
+    .iter()
+    .filter_map(|(part, answer)| answer.as_ref().map(|answer| (*part, answer)))
+    .map(|(part, answer)| {
+      let outcome = match &solved {
+        Err(e) => Outcome::Fail(e.clone()),
+        Ok(reports) => match reports.iter().find(|r| r.part == part) {
+          Some(r) if r.answer == *answer => Outcome::Pass,
+          Some(r) => Outcome::Regression { expected: answer.clone(), actual: r.answer.clone() },
+          None => Outcome::Fail("part was not solved".to_string()),
+        },
+      };
+
+      Verdict { day: expected.day, part, input: expected.input.clone(), outcome }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn workspace() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+  }
+
+  #[test]
+  fn parse_answers_test() {
+    let answers = parse_answers(r#"
+      [[answer]]
+      day = 1
+      input = "day1/input.txt"
+      part1 = "430"
+    "#).unwrap();
+
+    assert_eq!(answers.answer, vec![Expected {
+      day: 1,
+      input: PathBuf::from("day1/input.txt"),
+      part1: Some("430".to_string()),
+      part2: None,
+    }]);
+
+    assert_eq!(parse_answers("").unwrap().answer, vec![]);
+    assert!(parse_answers("[[answer]]\nday = \"one\"").is_err());
+  }
+
+  #[test]
+  fn verify_test() {
+    let expected = Expected {
+      day: 1,
+      input: PathBuf::from("day1/input.txt"),
+      part1: Some("430".to_string()),
+      part2: Some("0".to_string()),
+    };
+
+    assert_eq!(
+      verify(&expected, &workspace()).into_iter().map(|v| v.outcome).collect::<Vec<_>>(),
+      vec![
+        Outcome::Pass,
+        Outcome::Regression { expected: "0".to_string(), actual: "462".to_string() },
+      ]
+    );
+  }
+
+  #[test]
+  fn verify_missing_input_test() {
+    let expected = Expected {
+      day: 1,
+      input: PathBuf::from("day1/missing.txt"),
+      part1: Some("430".to_string()),
+      part2: None,
+    };
+    let verdicts = verify(&expected, &workspace());
+
+    assert_eq!(verdicts.len(), 1);
+    assert!(matches!(verdicts[0].outcome, Outcome::Fail(_)));
+  }
+
+  #[test]
+  fn checked_in_answers_test() {
+    let answers = read_answers(&workspace().join("answers.toml")).unwrap();
+
+    for expected in answers.answer.iter() {
+      for verdict in verify(expected, &workspace()) {
+        assert!(verdict.is_pass(), "{}", verdict);
+      }
+    }
+  }
+}
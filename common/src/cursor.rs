@@ -0,0 +1,184 @@
+use std::str::FromStr;
+
+use crate::error::ParseError;
+
+/// Walks a single line of puzzle input while keeping track of the position,
+/// so that every failed expectation is reported at the exact column.
+pub struct Cursor<'a> {
+  input: &'a str,
+  position: usize,
+}
+
+impl<'a> Cursor<'a> {
+  pub fn new(input: &'a str) -> Cursor<'a> {
+    Cursor { input, position: 0 }
+  }
+
+  pub fn rest(&self) -> &'a str {
+    &self.input[self.position..]
+  }
+
+  /// 1-based column of the next unread character.
+  pub fn column(&self) -> usize {
+    self.input[..self.position].chars().count() + 1
+  }
+
+  pub fn error(&self, message: &str) -> ParseError {
+    self.error_at(self.column(), message)
+  }
+
+  pub fn error_at(&self, column: usize, message: &str) -> ParseError {
+    ParseError::new(self.input, column, message)
+  }
+
+  fn found(&self) -> String {
+    match self.rest().chars().next() {
+      Some(c) => format!("found {:?}", c),
+      None => "found end of line".to_string(),
+    }
+  }
+
+  fn expected(&self, what: &str) -> ParseError {
+    self.error(&format!("expected {}, {}", what, self.found()))
+  }
+
+  pub fn is_next(&self, literal: &str) -> bool {
+    self.rest().starts_with(literal)
+  }
+
+  pub fn expect(&mut self, literal: &str) -> Result<(), ParseError> {
+    if !self.is_next(literal) {
+      return Err(self.expected(&format!("{:?}", literal)));
+    }
+
+    self.position += literal.len();
+    Ok(())
+  }
+
+  /// Consumes at least one whitespace character.
+  pub fn whitespace(&mut self) -> Result<(), ParseError> {
+    let len = self.rest().len() - self.rest().trim_start().len();
+
+    if len == 0 {
+      return Err(self.expected("whitespace"));
+    }
+
+    self.position += len;
+    Ok(())
+  }
+
+  pub fn char_if<F>(&mut self, what: &str, predicate: F) -> Result<char, ParseError>
+  where
+    F: Fn(char) -> bool,
+  {
+    match self.rest().chars().next() {
+      Some(c) if predicate(c) => {
+        self.position += c.len_utf8();
+        Ok(c)
+      },
+      _ => Err(self.expected(what)),
+    }
+  }
+
+  /// Consumes everything up to, but excluding, the first character matching
+  /// `delimiter` and returns it.
+  pub fn take_until<F>(&mut self, delimiter: F) -> &'a str
+  where
+    F: Fn(char) -> bool,
+  {
+    let rest = self.rest();
+    let len = rest.find(delimiter).unwrap_or(rest.len());
+
+    self.position += len;
+    &rest[..len]
+  }
+
+  /// Consumes an optionally signed run of ASCII digits.
+  pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+    let rest = self.rest();
+    let sign = if rest.starts_with('+') || rest.starts_with('-') { 1 } else { 0 };
+    let digits = rest[sign..]
+      .bytes()
+      .take_while(|b| b.is_ascii_digit())
+      .count();
+
+    if digits == 0 {
+      return Err(self.expected("number"));
+    }
+
+    let value = rest[..sign + digits]
+      .parse::<T>()
+      .map_err(|_| self.error(&format!("number {:?} is out of range", &rest[..sign + digits])))?;
+
+    self.position += sign + digits;
+    Ok(value)
+  }
+
+  /// Succeeds only when nothing but trailing whitespace is left.
+  pub fn end(&mut self) -> Result<(), ParseError> {
+    let trimmed = self.rest().trim_start();
+    self.position = self.input.len() - trimmed.len();
+
+    if !trimmed.is_empty() {
+      return Err(self.error(&format!("unexpected trailing input, {}", self.found())));
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn take_until_test() {
+    let mut cursor = Cursor::new("+1, -2");
+
+    assert_eq!(cursor.take_until(|c| c == ','), "+1");
+    assert_eq!(cursor.column(), 3);
+    assert!(cursor.expect(",").is_ok());
+    assert_eq!(cursor.take_until(|c| c == ','), " -2");
+    assert_eq!(cursor.take_until(|c| c == ','), "");
+  }
+
+  #[test]
+  fn expect_test() {
+    let mut cursor = Cursor::new("#1 @");
+
+    assert!(cursor.expect("#").is_ok());
+    assert_eq!(cursor.number::<usize>(), Ok(1));
+    assert!(cursor.whitespace().is_ok());
+    assert_eq!(cursor.column(), 4);
+
+    let e = cursor.expect(",").unwrap_err();
+    assert_eq!(e.column, 4);
+    assert_eq!(e.message, "expected \",\", found '@'".to_string());
+  }
+
+  #[test]
+  fn number_test() {
+    assert_eq!(Cursor::new("+12x").number::<isize>(), Ok(12));
+    assert_eq!(Cursor::new("-12").number::<isize>(), Ok(-12));
+    assert!(Cursor::new("-12").number::<usize>().is_err());
+    assert!(Cursor::new("x").number::<usize>().is_err());
+    assert!(Cursor::new("+").number::<usize>().is_err());
+    assert!(Cursor::new("99999999999999999999999").number::<usize>().is_err());
+  }
+
+  #[test]
+  fn column_test() {
+    let mut cursor = Cursor::new("🔥🔥x");
+
+    assert!(cursor.char_if("fire", |c| c == '🔥').is_ok());
+    assert!(cursor.char_if("fire", |c| c == '🔥').is_ok());
+    assert_eq!(cursor.column(), 3);
+    assert_eq!(cursor.char_if("fire", |c| c == '🔥').unwrap_err().column, 3);
+  }
+
+  #[test]
+  fn end_test() {
+    assert!(Cursor::new("  ").end().is_ok());
+    assert_eq!(Cursor::new(" x").end().unwrap_err().column, 2);
+  }
+}
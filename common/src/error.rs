@@ -0,0 +1,165 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Widest slice of an offending line shown when rendering a diagnostic.
+const RENDER_WIDTH: usize = 72;
+
+/// Location-aware description of why a piece of puzzle input was rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+  pub line: usize,
+  pub column: usize,
+  pub text: String,
+  pub message: String,
+}
+
+impl ParseError {
+  /// Both `line` and `column` are 1-based; line parsers report line 1 and
+  /// let the reader relocate the error with `at_line`.
+  pub fn new(text: &str, column: usize, message: &str) -> ParseError {
+    ParseError {
+      line: 1,
+      column,
+      text: text.to_string(),
+      message: message.to_string(),
+    }
+  }
+
+  pub fn at_line(self, line: usize) -> ParseError {
+    ParseError { line, ..self }
+  }
+
+  /// Renders the offending line with a caret under the failing column,
+  /// cutting very long lines down to a window around it.
+  pub fn render(&self) -> String {
+    let chars: Vec<char> = self.text.chars().collect();
+    let index = self.column.saturating_sub(1).min(chars.len());
+    let start = index.saturating_sub(RENDER_WIDTH / 2);
+    let end = (start + RENDER_WIDTH).min(chars.len());
+
+    let prefix = if start > 0 { "..." } else { "" };
+    let suffix = if end < chars.len() { "..." } else { "" };
+    let excerpt: String = chars[start..end].iter().collect();
+
+    let gutter = self.line.to_string();
+    let padding = " ".repeat(gutter.len());
+    let offset = " ".repeat(prefix.len() + index - start);
+
+    format!(
+      "{} | {}{}{}\n{} | {}^",
+      gutter, prefix, excerpt, suffix, padding, offset
+    )
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "line {}, column {}: {}\n{}",
+      self.line, self.column, self.message, self.render()
+    )
+  }
+}
+
+#[derive(Debug)]
+pub enum Error {
+  Io(io::Error),
+  Parse(ParseError),
+  NoSolution(String),
+  Ambiguous(String),
+  Overflow(String),
+  TooLarge(String),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::Io(e) => write!(f, "could not read input: {}", e),
+      Error::Parse(e) => write!(f, "could not parse input at {}", e),
+      Error::NoSolution(reason) => write!(f, "no solution: {}", reason),
+      Error::Ambiguous(reason) => write!(f, "ambiguous solution: {}", reason),
+      Error::Overflow(reason) => write!(f, "arithmetic overflow: {}", reason),
+      Error::TooLarge(reason) => write!(f, "too large: {}", reason),
+    }
+  }
+}
+
+impl error::Error for Error {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      Error::Io(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(e: io::Error) -> Error {
+    Error::Io(e)
+  }
+}
+
+impl From<ParseError> for Error {
+  fn from(e: ParseError) -> Error {
+    Error::Parse(e)
+  }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_error_display_test() {
+    let e = ParseError::new("#1 @ 1,1 1x1", 9, "expected \":\"").at_line(12);
+
+    assert_eq!(e.line, 12);
+    assert_eq!(
+      e.to_string(),
+      "line 12, column 9: expected \":\"\n12 | #1 @ 1,1 1x1\n   |         ^".to_string()
+    );
+    assert_eq!(
+      Error::from(e).to_string(),
+      "could not parse input at line 12, column 9: expected \":\"\n12 | #1 @ 1,1 1x1\n   |         ^"
+        .to_string()
+    );
+  }
+
+  #[test]
+  fn render_test() {
+    assert_eq!(ParseError::new("", 1, "empty").render(), "1 | \n  | ^".to_string());
+    assert_eq!(ParseError::new("ab", 3, "short").render(), "1 | ab\n  |   ^".to_string());
+
+    let long = "a".repeat(100) + "!" + &"b".repeat(100);
+    let rendered = ParseError::new(&long, 101, "bang").render();
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert!(lines[0].starts_with("1 | ...a"));
+    assert!(lines[0].ends_with("b..."));
+    assert_eq!(lines[0].find('!'), lines[1].find('^'));
+  }
+
+  #[test]
+  fn no_solution_display_test() {
+    assert_eq!(
+      Error::NoSolution("empty input".to_string()).to_string(),
+      "no solution: empty input".to_string()
+    );
+    assert_eq!(
+      Error::Ambiguous("2 pairs match".to_string()).to_string(),
+      "ambiguous solution: 2 pairs match".to_string()
+    );
+    assert_eq!(
+      Error::Overflow("frequency leaves isize at line 3".to_string()).to_string(),
+      "arithmetic overflow: frequency leaves isize at line 3".to_string()
+    );
+    assert_eq!(
+      Error::TooLarge("2000x2000 image".to_string()).to_string(),
+      "too large: 2000x2000 image".to_string()
+    );
+  }
+}
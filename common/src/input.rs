@@ -0,0 +1,131 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+use crate::error::{ParseError, Result};
+
+/// Where a day's puzzle input comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+  Stdin,
+  Path(PathBuf),
+  Inline(String),
+}
+
+impl Source {
+  /// Interprets a command line argument: `-` is stdin, anything else a path.
+  pub fn from_arg(arg: &str) -> Source {
+    match arg {
+      "-" => Source::Stdin,
+      path => Source::Path(PathBuf::from(path)),
+    }
+  }
+
+  pub fn reader(&self) -> io::Result<Box<dyn BufRead + '_>> {
+    match self {
+      Source::Stdin => Ok(Box::new(io::stdin().lock())),
+      Source::Path(path) => Ok(Box::new(BufReader::new(File::open(path)?))),
+      Source::Inline(contents) => Ok(Box::new(contents.as_bytes())),
+    }
+  }
+}
+
+impl fmt::Display for Source {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Source::Stdin => write!(f, "-"),
+      Source::Path(path) => write!(f, "{}", path.display()),
+      Source::Inline(_) => write!(f, "<inline>"),
+    }
+  }
+}
+
+/// Parses every line of `reader` with `parse`, relocating parse errors to
+/// the line they were found on.
+pub fn parse_lines<R, T, F>(reader: R, parse: F) -> Result<Vec<T>>
+where
+  R: BufRead,
+  F: Fn(&String) -> std::result::Result<T, ParseError>,
+{
+  reader
+    .lines()
+    .enumerate()
+    .map(|(i, line_r)| Ok(parse(&line_r?).map_err(|e| e.at_line(i + 1))?))
+    .collect()
+}
+
+/// Like `parse_lines`, but hands every parsed line and its 1-based number
+/// to `fold` as soon as it is read, so the input never has to fit in memory.
+pub fn fold_lines<R, T, A, P, F>(reader: R, init: A, parse: P, mut fold: F) -> Result<A>
+where
+  R: BufRead,
+  P: Fn(&String) -> std::result::Result<T, ParseError>,
+  F: FnMut(A, T, usize) -> Result<A>,
+{
+  reader
+    .lines()
+    .enumerate()
+    .try_fold(init, |acc, (i, line_r)| {
+      let value = parse(&line_r?).map_err(|e| e.at_line(i + 1))?;
+      fold(acc, value, i + 1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Read;
+
+  #[test]
+  fn from_arg_test() {
+    assert_eq!(Source::from_arg("-"), Source::Stdin);
+    assert_eq!(Source::from_arg("day1/input.txt"), Source::Path(PathBuf::from("day1/input.txt")));
+  }
+
+  #[test]
+  fn reader_test() {
+    let source = Source::Inline("+1\n-2\n".to_string());
+    let mut contents = String::new();
+    source.reader().unwrap().read_to_string(&mut contents).unwrap();
+
+    assert_eq!(contents, "+1\n-2\n".to_string());
+    assert!(Source::Path(PathBuf::from("does/not/exist")).reader().is_err());
+  }
+
+  #[test]
+  fn parse_lines_test() {
+    let parse = |line: &String| line
+      .parse::<usize>()
+      .map_err(|_| ParseError::new(line, 1, "not a number"));
+
+    assert_eq!(parse_lines("1\n2\n3".as_bytes(), parse).unwrap(), vec![1, 2, 3]);
+
+    match parse_lines("1\nx\n3".as_bytes(), parse) {
+      Err(crate::Error::Parse(e)) => assert_eq!((e.line, e.text), (2, "x".to_string())),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn fold_lines_test() {
+    let parse = |line: &String| line
+      .parse::<usize>()
+      .map_err(|_| ParseError::new(line, 1, "not a number"));
+    let sum = |acc: usize, n: usize, _| Ok(acc + n);
+
+    assert_eq!(fold_lines("1\n2\n3".as_bytes(), 0, parse, sum).unwrap(), 6);
+    assert_eq!(fold_lines("".as_bytes(), 0, parse, sum).unwrap(), 0);
+
+    match fold_lines("1\n2\nx".as_bytes(), 0, parse, sum) {
+      Err(crate::Error::Parse(e)) => assert_eq!(e.line, 3),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    let lines = fold_lines("5\n6".as_bytes(), vec![], parse, |mut acc, _, line| {
+      acc.push(line);
+      Ok(acc)
+    });
+    assert_eq!(lines.unwrap(), vec![1, 2]);
+  }
+}
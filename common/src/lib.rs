@@ -0,0 +1,34 @@
+mod cursor;
+mod error;
+mod input;
+mod rng;
+
+use std::fmt::Display;
+use std::io::BufRead;
+
+pub use cursor::Cursor;
+pub use error::{Error, ParseError, Result};
+pub use input::{fold_lines, parse_lines, Source};
+pub use rng::Rng;
+
+/// A single day's puzzle: how its input is read and how both parts are
+/// answered. Runners, benchmarks and verifiers are written once against
+/// this trait instead of against each day's free functions.
+pub trait Solution {
+  const DAY: u8;
+
+  type Input;
+  type Output: Display;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input>;
+  fn part1(input: &Self::Input) -> Result<Self::Output>;
+  fn part2(input: &Self::Input) -> Result<Self::Output>;
+
+  fn parse_str(input: &str) -> Result<Self::Input> {
+    Self::parse(input.as_bytes())
+  }
+
+  fn read(source: &Source) -> Result<Self::Input> {
+    Self::parse(source.reader()?)
+  }
+}
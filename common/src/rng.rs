@@ -0,0 +1,78 @@
+/// Small seeded pseudo-random generator (SplitMix64) used to synthesise
+/// puzzle inputs. It is kept in-tree so a given seed produces the same
+/// input on every machine and across dependency upgrades.
+pub struct Rng {
+  state: u64,
+}
+
+impl Rng {
+  pub fn new(seed: u64) -> Rng {
+    Rng { state: seed }
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /// Uniform value in `0..n`; `n` must be positive.
+  pub fn below(&mut self, n: u64) -> u64 {
+    self.next_u64() % n
+  }
+
+  /// Uniform value in the inclusive range `low..=high`.
+  pub fn range(&mut self, low: i64, high: i64) -> i64 {
+    low + self.below((high - low + 1) as u64) as i64
+  }
+
+  pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+    self.below(denominator) < numerator
+  }
+
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      let j = self.below(i as u64 + 1) as usize;
+      items.swap(i, j);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn determinism_test() {
+    let a: Vec<u64> = (0..5).scan(Rng::new(42), |rng, _| Some(rng.next_u64())).collect();
+    let b: Vec<u64> = (0..5).scan(Rng::new(42), |rng, _| Some(rng.next_u64())).collect();
+    let c: Vec<u64> = (0..5).scan(Rng::new(43), |rng, _| Some(rng.next_u64())).collect();
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+  }
+
+  #[test]
+  fn range_test() {
+    let mut rng = Rng::new(7);
+
+    for _ in 0..1000 {
+      let v = rng.range(-3, 3);
+      assert!((-3..=3).contains(&v));
+    }
+  }
+
+  #[test]
+  fn shuffle_test() {
+    let mut items: Vec<usize> = (0..100).collect();
+    Rng::new(1).shuffle(&mut items);
+
+    assert_ne!(items, (0..100).collect::<Vec<usize>>());
+
+    items.sort();
+    assert_eq!(items, (0..100).collect::<Vec<usize>>());
+  }
+}
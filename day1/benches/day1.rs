@@ -0,0 +1,38 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::Solution;
+use day1::Day1;
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// Repeats the frequency changes `factor` times.
+fn scale(factor: usize) -> String {
+  vec![INPUT.trim_end(); factor].join("\n")
+}
+
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day1");
+  group.sample_size(10);
+
+  for factor in [1, 4, 16].iter() {
+    let text = scale(*factor);
+    let input = Day1::parse_str(&text).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", factor), &text, |b, text| {
+      b.iter(|| Day1::parse_str(black_box(text)))
+    });
+    group.bench_with_input(BenchmarkId::new("part1", factor), &input, |b, input| {
+      b.iter(|| Day1::part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", factor), &input, |b, input| {
+      b.iter(|| Day1::part2(black_box(input)))
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -1,43 +1,452 @@
 use std::fs::File;
-use std::io::{BufReader, BufRead, Error};
-use std::collections::HashSet;
+use std::io::{BufReader, BufRead};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::iter;
+use std::str::FromStr;
 
-pub fn read_input(filepath: &str) -> Result<Vec<isize>, Error>{
-  let file = File::open(filepath)?;
-  let lines: Vec<isize> = BufReader::new(file)
-    .lines()
-    .map(|line| line.unwrap().parse::<isize>().unwrap())
-    .collect();
+use num_bigint::BigInt;
 
-  Ok(lines)
+use common::{fold_lines, parse_lines, Cursor, Error, ParseError, Result, Rng, Solution};
+
+/// Reads the frequency changes on a line. Besides one change per line, the
+/// puzzle's examples list several separated by commas, whitespace or both,
+/// e.g. `+1, -2, +3` or `+1 -2 +3`.
+fn parse_changes<T>(input: &String) -> std::result::Result<Vec<T>, ParseError>
+where
+  T: FromStr,
+  T::Err: Display,
+{
+  let mut cursor = Cursor::new(input);
+  let mut changes: Vec<T> = Vec::new();
+
+  loop {
+    cursor.whitespace().ok();
+
+    let column = cursor.column();
+    let token = cursor.take_until(|c| c == ',' || c.is_whitespace());
+
+    if token.is_empty() {
+      return Err(cursor.error("expected a frequency change"));
+    }
+
+    changes.push(token
+      .parse::<T>()
+      .map_err(|e| cursor.error_at(column, &format!("invalid frequency change {:?}: {}", token, e)))?);
+
+    if cursor.end().is_ok() {
+      return Ok(changes);
+    }
+
+    if cursor.is_next(",") {
+      cursor.expect(",")?;
+    }
+  }
+}
+
+fn parse(input: &String) -> std::result::Result<Vec<isize>, ParseError> {
+  parse_changes(input)
+}
+
+pub fn read_from<R: BufRead>(reader: R) -> Result<Vec<isize>> {
+  Ok(parse_lines(reader, parse)?.into_iter().flatten().collect())
+}
+
+pub fn parse_str(input: &str) -> Result<Vec<isize>> {
+  read_from(input.as_bytes())
+}
+
+pub fn read_input(filepath: &str) -> Result<Vec<isize>> {
+  read_from(BufReader::new(File::open(filepath)?))
+}
+
+fn overflow(location: String) -> Error {
+  Error::Overflow(format!("frequency leaves the isize range at {}", location))
+}
+
+/// Sums the changes, reporting an overflow instead of wrapping around.
+pub fn calc_part1(input: &Vec<isize>) -> Result<isize> {
+  input
+    .iter()
+    .enumerate()
+    .try_fold(0_isize, |frequency, (i, change)| frequency
+      .checked_add(*change)
+      .ok_or_else(|| overflow(format!("change #{}", i + 1))))
+}
+
+/// Sums the changes as they are read from `reader`, without collecting them.
+pub fn stream_part1<R: BufRead>(reader: R) -> Result<isize> {
+  fold_lines(reader, 0_isize, parse, |frequency, changes, line| changes
+    .into_iter()
+    .try_fold(frequency, |frequency, change| frequency
+      .checked_add(change)
+      .ok_or_else(|| overflow(format!("line {}", line)))))
 }
 
-pub fn calc_part1(input: &Vec<isize>) -> isize {
-  input.iter().fold(0, |a, b| a + b)
+/// Streaming sum with arbitrary precision, for inputs whose changes or
+/// total do not fit in an `isize`.
+pub fn stream_part1_big<R: BufRead>(reader: R) -> Result<BigInt> {
+  fold_lines(reader, BigInt::default(), parse_changes::<BigInt>, |frequency, changes, _| {
+    Ok(changes.into_iter().fold(frequency, |frequency, change| frequency + change))
+  })
 }
 
-pub fn calc_part2(input: &Vec<isize>) -> isize {
-  let mut cycle = input.iter().cycle();
-  let mut result = 0;
-  let mut set: HashSet<isize> = HashSet::new();
+/// The first frequency reached twice: after `cycles` full passes over the
+/// changes and `index` more, the device reads `frequency` again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Repeat {
+  pub frequency: isize,
+  pub cycles: usize,
+  pub index: usize,
+}
 
-  while !set.contains(&result) {
-    set.insert(result);
-    result += cycle.next().unwrap();
+/// How the frequency evolves over the first few passes over the changes.
+#[derive(Debug, PartialEq)]
+pub struct DriftReport {
+  /// Frequency gained by every full pass.
+  pub drift: isize,
+  /// `None` when no frequency is ever reached twice.
+  pub first_repeat: Option<Repeat>,
+  pub min: isize,
+  pub max: isize,
+  /// Frequencies reached twice within the analysed passes, in the order
+  /// their second visit happens.
+  pub repeated: Vec<isize>,
+}
+
+/// Finds when the first repeated frequency is reached.
+///
+/// After the first pass every frequency is `sum + k * drift` for one of the
+/// first pass' prefix sums, so a later repeat can only happen between prefix
+/// sums that share a residue modulo the drift. Within such a group the
+/// nearest neighbours in value decide how many cycles it takes, which keeps
+/// this O(n log n) however many cycles the device goes through.
+pub fn calc_first_repeat(input: &Vec<isize>) -> Result<Repeat> {
+  if input.is_empty() {
+    return Err(Error::NoSolution("no frequency changes".to_string()));
   }
 
-  result
+  let mut seen: HashSet<isize> = HashSet::new();
+  let mut sums: Vec<isize> = Vec::with_capacity(input.len());
+  let mut frequency = 0;
+
+  for (index, change) in input.iter().enumerate() {
+    if !seen.insert(frequency) {
+      return Ok(Repeat { frequency, cycles: 0, index });
+    }
+
+    sums.push(frequency);
+    frequency = frequency
+      .checked_add(*change)
+      .ok_or_else(|| overflow(format!("change #{}", index + 1)))?;
+  }
+
+  let drift = frequency;
+
+  if drift == 0 {
+    return Ok(Repeat { frequency: 0, cycles: 1, index: 0 });
+  }
+
+  // Residues and gaps are taken in i128, where they cannot overflow even
+  // for prefix sums at opposite ends of the isize range.
+  let mut groups: HashMap<i128, Vec<(isize, usize)>> = HashMap::new();
+
+  for (i, sum) in sums.iter().enumerate() {
+    groups.entry((*sum as i128).rem_euclid(drift as i128)).or_default().push((*sum, i));
+  }
+
+  for group in groups.values_mut() {
+    group.sort_unstable();
+  }
+
+  // Each candidate is (cycles, index within the cycle, frequency repeated).
+  let candidates = groups
+    .values()
+    .flat_map(|group| group.windows(2).map(|w| {
+      let ((low, i), (high, j)) = (w[0], w[1]);
+      let cycles = usize::try_from(((high as i128 - low as i128) / drift as i128).unsigned_abs())
+        .map_err(|_| Error::Overflow(format!("frequency {} repeats after too many cycles", high)))?;
+
+      Ok(match drift > 0 {
+        true => (cycles, i, high),
+        false => (cycles, j, low),
+      })
+    }))
+    .collect::<Result<Vec<(usize, usize, isize)>>>()?;
+
+  let (cycles, index, frequency) = candidates
+    .into_iter()
+    .min()
+    .ok_or_else(|| Error::NoSolution("frequencies never repeat".to_string()))?;
+
+  // Every pass shifts the prefix sums by the drift, so the farthest the
+  // device gets is at the end of the last full pass or just before the repeat.
+  let farthest = |sums: &[isize], k: usize| match drift > 0 {
+    true => *sums.iter().max().unwrap() as i128 + k as i128 * drift as i128,
+    false => *sums.iter().min().unwrap() as i128 + k as i128 * drift as i128,
+  };
+
+  for reached in [farthest(&sums, cycles - 1), farthest(&sums[..=index], cycles)].iter() {
+    if isize::try_from(*reached).is_err() {
+      return Err(Error::Overflow(format!("frequency leaves the isize range before {} repeats", frequency)));
+    }
+  }
+
+  Ok(Repeat { frequency, cycles, index })
+}
+
+pub fn calc_part2(input: &Vec<isize>) -> Result<isize> {
+  calc_first_repeat(input).map(|repeat| repeat.frequency)
+}
+
+/// Reports on the frequencies reached during the first `cycles` passes,
+/// starting from 0 and including the frequency after the last change.
+pub fn calc_drift_report(input: &Vec<isize>, cycles: usize) -> Result<DriftReport> {
+  let first_repeat = match calc_first_repeat(input) {
+    Ok(repeat) => Some(repeat),
+    Err(Error::NoSolution(_)) if !input.is_empty() => None,
+    Err(e) => return Err(e),
+  };
+
+  let changes = cycles
+    .checked_mul(input.len())
+    .ok_or_else(|| Error::Overflow(format!("{} passes over {} changes", cycles, input.len())))?;
+
+  let frequencies = iter::once(Ok(0)).chain(input
+    .iter()
+    .cycle()
+    .take(changes)
+    .enumerate()
+    .scan(0_isize, |frequency, (i, change)| {
+      let next = frequency
+        .checked_add(*change)
+        .ok_or_else(|| overflow(format!("change #{} of pass #{}", i % input.len() + 1, i / input.len() + 1)));
+
+      *frequency = *next.as_ref().unwrap_or(frequency);
+      Some(next)
+    }));
+
+  let mut visits: HashMap<isize, usize> = HashMap::new();
+  let mut repeated: Vec<isize> = Vec::new();
+  let (mut min, mut max) = (0, 0);
+
+  for frequency in frequencies {
+    let frequency = frequency?;
+    min = min.min(frequency);
+    max = max.max(frequency);
+
+    let count = visits.entry(frequency).or_insert(0);
+    *count += 1;
+
+    if *count == 2 {
+      repeated.push(frequency);
+    }
+  }
+
+  Ok(DriftReport { drift: calc_part1(input)?, first_repeat, min, max, repeated })
+}
+
+/// Synthesises `size` frequency changes between -20 and +20.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+
+  (0..size)
+    .map(|_| match rng.range(-20, 19) {
+      v if v >= 0 => format!("+{}", v + 1),
+      v => v.to_string(),
+    })
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+  const DAY: u8 = 1;
+
+  type Input = Vec<isize>;
+  type Output = isize;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    calc_part1(input)
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    calc_part2(input)
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_parse() {
+    assert_eq!(parse(&"+7".to_string()), Ok(vec![7]));
+    assert_eq!(parse(&"-13".to_string()), Ok(vec![-13]));
+    assert_eq!(parse(&"13".to_string()), Ok(vec![13]));
+    assert!(parse(&"".to_string()).is_err());
+    assert!(parse(&"+-1".to_string()).is_err());
+  }
+
+  #[test]
+  fn test_parse_notations() {
+    assert_eq!(parse(&"+1, -2, +3".to_string()), Ok(vec![1, -2, 3]));
+    assert_eq!(parse(&"+1 -2\t+3 ".to_string()), Ok(vec![1, -2, 3]));
+    assert_eq!(parse(&" +1,-2 ,+3".to_string()), Ok(vec![1, -2, 3]));
+
+    let e = parse(&"+1, -2, x3, +4".to_string()).unwrap_err();
+    assert_eq!(e.column, 9);
+    assert!(e.message.starts_with("invalid frequency change \"x3\""));
+
+    assert_eq!(parse(&"+1,, +3".to_string()).unwrap_err().column, 4);
+    assert_eq!(parse(&"+1, -2,".to_string()).unwrap_err().column, 8);
+
+    assert_eq!(parse_str("+1, -2\n+3 +4\n-5\n").unwrap(), vec![1, -2, 3, 4, -5]);
+    assert_eq!(stream_part1("+1, -2\n+3 +4\n".as_bytes()).unwrap(), 6);
+    assert_eq!(stream_part1_big("+1, -2\n+3 +4\n".as_bytes()).unwrap(), BigInt::from(6));
+  }
+
+  #[test]
+  fn test_parse_str() {
+    assert_eq!(parse_str("+1\n-2\n+3\n").unwrap(), vec![1, -2, 3]);
+
+    match parse_str("+1\n-2\n3+\n") {
+      Err(Error::Parse(e)) => assert_eq!(e.line, 3),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn test_part1() {
+    let max = isize::MAX.to_string();
+
+    assert_eq!(calc_part1(&vec![1, -2, 3]).unwrap(), 2);
+    assert_eq!(calc_part1(&vec![isize::MAX, 1, -1]).map_err(|e| e.to_string()).unwrap_err(),
+      "arithmetic overflow: frequency leaves the isize range at change #2".to_string());
+
+    assert_eq!(stream_part1("+1\n-2\n+3\n".as_bytes()).unwrap(), 2);
+    assert_eq!(stream_part1("".as_bytes()).unwrap(), 0);
+
+    match stream_part1(format!("+1\n{}\n-5", max).as_bytes()) {
+      Err(Error::Overflow(reason)) => assert!(reason.ends_with("line 2")),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    match stream_part1("+1\n+x".as_bytes()) {
+      Err(Error::Parse(e)) => assert_eq!(e.line, 2),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    let big = stream_part1_big(format!("{}\n{}\n+2\n-1", max, max).as_bytes()).unwrap();
+    assert_eq!(big, BigInt::from(isize::MAX) * 2 + 1);
+    assert_eq!(stream_part1_big("+100000000000000000000000000\n-1".as_bytes()).unwrap().to_string(),
+      "99999999999999999999999999".to_string());
+  }
+
   #[test]
   fn test_part2() {
-    assert_eq!(calc_part2(&vec![1, -1]), 0);
-    assert_eq!(calc_part2(&vec![3, 3, 4, -2, -4]), 10);
-    assert_eq!(calc_part2(&vec![-6, 3, 8, 5, -6]), 5);
-    assert_eq!(calc_part2(&vec![7, 7, -2, -7, -4]), 14);
+    assert_eq!(calc_part2(&vec![1, -1]).unwrap(), 0);
+    assert_eq!(calc_part2(&vec![3, 3, 4, -2, -4]).unwrap(), 10);
+    assert_eq!(calc_part2(&vec![-6, 3, 8, 5, -6]).unwrap(), 5);
+    assert_eq!(calc_part2(&vec![7, 7, -2, -7, -4]).unwrap(), 14);
+    assert_eq!(calc_part2(&vec![1, 2, -2]).unwrap(), 1);
+    assert_eq!(calc_part2(&vec![-1, -2, 4]).unwrap(), 0);
+    assert_eq!(calc_part2(&vec![-1, -2, 2]).unwrap(), -1);
+    assert_eq!(calc_part2(&vec![10_000_000, -9_999_999]).unwrap(), 10_000_000);
+    assert!(calc_part2(&vec![]).is_err());
+
+    match calc_part2(&vec![1, 1]) {
+      Err(Error::NoSolution(_)) => (),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn test_first_repeat() {
+    assert_eq!(calc_first_repeat(&vec![1, 2, -2]).unwrap(), Repeat { frequency: 1, cycles: 1, index: 0 });
+    assert_eq!(calc_first_repeat(&vec![1, -1]).unwrap(), Repeat { frequency: 0, cycles: 1, index: 0 });
+    assert_eq!(calc_first_repeat(&vec![1, -2, 2, 5]).unwrap(), Repeat { frequency: 1, cycles: 0, index: 3 });
+    assert_eq!(calc_first_repeat(&vec![3, 3, 4, -2, -4]).unwrap(), Repeat { frequency: 10, cycles: 1, index: 2 });
+  }
+
+  #[test]
+  fn test_first_repeat_overflow() {
+    assert!(matches!(calc_first_repeat(&vec![isize::MAX, 1]), Err(Error::Overflow(_))));
+    assert!(matches!(calc_first_repeat(&vec![isize::MIN, isize::MAX]), Err(Error::Overflow(_))));
+    assert!(matches!(calc_first_repeat(&vec![isize::MAX, 1 - isize::MAX]), Err(Error::Overflow(_))));
+    assert!(matches!(calc_part2(&vec![isize::MAX, 1]), Err(Error::Overflow(_))));
+
+    assert_eq!(
+      calc_first_repeat(&vec![isize::MAX - 2, 1, -isize::MAX]).unwrap(),
+      Repeat { frequency: isize::MAX - 2, cycles: 1, index: 2 }
+    );
+  }
+
+  #[test]
+  fn test_drift_report() {
+    assert_eq!(calc_drift_report(&vec![3, 3, 4, -2, -4], 2).unwrap(), DriftReport {
+      drift: 4,
+      first_repeat: Some(Repeat { frequency: 10, cycles: 1, index: 2 }),
+      min: 0,
+      max: 14,
+      repeated: vec![10, 8],
+    });
+
+    let report = calc_drift_report(&vec![1, 1], 3).unwrap();
+    assert_eq!(report.first_repeat, None);
+    assert_eq!((report.min, report.max, report.drift), (0, 6, 2));
+    assert!(report.repeated.is_empty());
+
+    assert!(calc_drift_report(&vec![], 3).is_err());
+    assert!(matches!(calc_drift_report(&vec![isize::MAX, -isize::MAX, 1], 2), Err(Error::Overflow(_))));
+    assert!(matches!(calc_drift_report(&vec![1, 1], usize::MAX), Err(Error::Overflow(_))));
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_generate() {
+    assert_eq!(generate(1000, 7), generate(1000, 7));
+    assert_eq!(parse_str(&generate(1000, 7)).unwrap().len(), 1000);
+    assert!(parse_str(&generate(1000, 7)).unwrap().iter().all(|v| *v != 0 && v.abs() <= 20));
+  }
+}
+
+#[cfg(test)]
+mod proptests {
+  use super::*;
+  use proptest::prelude::*;
+
+  /// The original simulation, given up on after `limit` steps.
+  fn simulate(input: &Vec<isize>, limit: usize) -> Option<(isize, usize)> {
+    let mut seen: HashSet<isize> = HashSet::new();
+    let mut frequency = 0;
+
+    for (step, change) in input.iter().cycle().take(limit).enumerate() {
+      if !seen.insert(frequency) {
+        return Some((frequency, step));
+      }
+      frequency += change;
+    }
+
+    None
+  }
+
+  proptest! {
+    #[test]
+    fn calc_first_repeat_matches_simulation(input in prop::collection::vec(-10..10_isize, 1..20)) {
+      match (calc_first_repeat(&input), simulate(&input, 100_000)) {
+        (Ok(repeat), Some((frequency, step))) => {
+          prop_assert_eq!(repeat.frequency, frequency);
+          prop_assert_eq!(repeat.cycles * input.len() + repeat.index, step);
+        },
+        (Ok(_), None) => (),
+        (Err(_), simulated) => prop_assert_eq!(simulated, None),
+      }
+    }
+  }
+}
@@ -0,0 +1,42 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::Solution;
+use day2::{generate, Day2};
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// The real box ids, or `factor` times as many synthetic ones. Copies of
+/// the real ids would repeat its matching pair and make part 2 ambiguous.
+fn scale(factor: usize) -> String {
+  match factor {
+    1 => INPUT.to_string(),
+    _ => generate(INPUT.lines().count() * factor, factor as u64),
+  }
+}
+
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day2");
+  group.sample_size(10);
+
+  for factor in [1, 4, 16].iter() {
+    let text = scale(*factor);
+    let input = Day2::parse_str(&text).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", factor), &text, |b, text| {
+      b.iter(|| Day2::parse_str(black_box(text)))
+    });
+    group.bench_with_input(BenchmarkId::new("part1", factor), &input, |b, input| {
+      b.iter(|| Day2::part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", factor), &input, |b, input| {
+      b.iter(|| Day2::part2(black_box(input)))
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+use crate::IdOptions;
+
+/// Positions at which two ids differ, with every character past the end of
+/// the shorter id counted as a mismatch, which keeps it a metric for ids of
+/// any length. Agrees with `hamming_distance` on equally long ids.
+pub fn distance(a: &String, b: &String) -> usize {
+  distance_with(a, b, &IdOptions::default())
+}
+
+/// `distance` counting characters as `options` sees them.
+pub fn distance_with(a: &String, b: &String, options: &IdOptions) -> usize {
+  let (a, b) = (options.segment(a), options.segment(b));
+  let mismatches = a.iter().zip(b.iter()).filter(|(x, y)| !options.same(x, y)).count();
+
+  mismatches + a.len().abs_diff(b.len())
+}
+
+struct Node {
+  id: String,
+  removed: bool,
+  children: BTreeMap<usize, usize>,
+}
+
+/// Burkhard-Keller tree over box ids, answering "which known ids are
+/// closest to this one" without comparing against every id.
+///
+/// Removed ids stay in the tree as tombstones so that the subtrees hanging
+/// off them remain reachable; inserting the same id again revives it.
+#[derive(Default)]
+pub struct BkTree {
+  nodes: Vec<Node>,
+  len: usize,
+  options: IdOptions,
+}
+
+impl BkTree {
+  pub fn new() -> BkTree {
+    BkTree::default()
+  }
+
+  /// An empty tree measuring distances between ids as `options` sees them.
+  pub fn with_options(options: IdOptions) -> BkTree {
+    BkTree { options, ..BkTree::default() }
+  }
+
+  fn distance(&self, a: &String, b: &String) -> usize {
+    distance_with(a, b, &self.options)
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Adds `id`, returning whether it was not already present.
+  pub fn insert(&mut self, id: &String) -> bool {
+    if self.nodes.is_empty() {
+      self.push(id);
+      return true;
+    }
+
+    let mut current = 0;
+
+    loop {
+      let d = self.distance(id, &self.nodes[current].id);
+
+      if d == 0 {
+        let node = &mut self.nodes[current];
+        let revived = node.removed;
+
+        node.removed = false;
+        self.len += revived as usize;
+        return revived;
+      }
+
+      match self.nodes[current].children.get(&d) {
+        Some(&child) => current = child,
+        None => {
+          let child = self.push(id);
+          self.nodes[current].children.insert(d, child);
+          return true;
+        },
+      }
+    }
+  }
+
+  /// Drops `id`, returning whether it was present.
+  pub fn remove(&mut self, id: &String) -> bool {
+    match self.find(id) {
+      Some(i) if !self.nodes[i].removed => {
+        self.nodes[i].removed = true;
+        self.len -= 1;
+        true
+      },
+      _ => false,
+    }
+  }
+
+  pub fn contains(&self, id: &String) -> bool {
+    self.find(id).is_some_and(|i| !self.nodes[i].removed)
+  }
+
+  /// Every id at most `k` away from `id`, closest first.
+  pub fn within(&self, id: &String, k: usize) -> Vec<(usize, &String)> {
+    let mut found: Vec<(usize, &String)> = Vec::new();
+    let mut stack: Vec<usize> = if self.nodes.is_empty() { vec![] } else { vec![0] };
+
+    while let Some(i) = stack.pop() {
+      let node = &self.nodes[i];
+      let d = self.distance(id, &node.id);
+
+      if d <= k && !node.removed {
+        found.push((d, &node.id));
+      }
+
+      stack.extend(node.children.range(d.saturating_sub(k)..=d.saturating_add(k)).map(|(_, &child)| child));
+    }
+
+    found.sort();
+    found
+  }
+
+  /// The id closest to `id`, ties going to the smallest id.
+  pub fn nearest(&self, id: &String) -> Option<(usize, &String)> {
+    let mut best: Option<(usize, &String)> = None;
+    let mut stack: Vec<usize> = if self.nodes.is_empty() { vec![] } else { vec![0] };
+
+    while let Some(i) = stack.pop() {
+      let node = &self.nodes[i];
+      let d = self.distance(id, &node.id);
+
+      if !node.removed && best.is_none_or(|b| (d, &node.id) < b) {
+        best = Some((d, &node.id));
+      }
+
+      let radius = best.map_or(usize::MAX, |(d, _)| d);
+      stack.extend(node.children
+        .range(d.saturating_sub(radius)..=d.saturating_add(radius))
+        .map(|(_, &child)| child));
+    }
+
+    best
+  }
+
+  fn push(&mut self, id: &String) -> usize {
+    self.nodes.push(Node { id: id.clone(), removed: false, children: BTreeMap::new() });
+    self.len += 1;
+    self.nodes.len() - 1
+  }
+
+  fn find(&self, id: &String) -> Option<usize> {
+    let mut current = if self.nodes.is_empty() { None } else { Some(0) };
+
+    while let Some(i) = current {
+      match self.distance(id, &self.nodes[i].id) {
+        0 => return Some(i),
+        d => current = self.nodes[i].children.get(&d).cloned(),
+      }
+    }
+
+    None
+  }
+}
+
+impl FromIterator<String> for BkTree {
+  fn from_iter<I: IntoIterator<Item = String>>(ids: I) -> BkTree {
+    let mut tree = BkTree::new();
+
+    for id in ids {
+      tree.insert(&id);
+    }
+
+    tree
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tree() -> BkTree {
+    ["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz"]
+      .iter()
+      .map(|id| id.to_string())
+      .collect()
+  }
+
+  #[test]
+  fn distance_test() {
+    assert_eq!(distance(&String::from("abc"), &String::from("abc")), 0);
+    assert_eq!(distance(&String::from("abc"), &String::from("abd")), 1);
+    assert_eq!(distance(&String::from("abc"), &String::from("ab")), 1);
+    assert_eq!(distance(&String::from("abc"), &String::from("")), 3);
+  }
+
+  #[test]
+  fn options_test() {
+    let graphemes = IdOptions { graphemes: true, ..IdOptions::default() };
+    let fold_case = IdOptions { fold_case: true, ..IdOptions::default() };
+
+    assert_eq!(distance(&String::from("ae\u{301}b"), &String::from("ab")), 3);
+    assert_eq!(distance_with(&String::from("ae\u{301}b"), &String::from("ab"), &graphemes), 2);
+
+    let mut tree = BkTree::with_options(fold_case);
+    tree.insert(&String::from("abcde"));
+    tree.insert(&String::from("fghij"));
+
+    assert!(!tree.insert(&String::from("ABCDE")));
+    assert!(tree.contains(&String::from("FGHIJ")));
+    assert_eq!(tree.nearest(&String::from("ABCDX")), Some((1, &String::from("abcde"))));
+  }
+
+  #[test]
+  fn insert_remove_test() {
+    let mut tree = tree();
+
+    assert_eq!(tree.len(), 7);
+    assert!(!tree.insert(&String::from("abcde")));
+    assert!(tree.contains(&String::from("fguij")));
+
+    assert!(tree.remove(&String::from("fguij")));
+    assert!(!tree.remove(&String::from("fguij")));
+    assert!(!tree.contains(&String::from("fguij")));
+    assert_eq!(tree.len(), 6);
+
+    assert!(tree.insert(&String::from("fguij")));
+    assert!(tree.contains(&String::from("fguij")));
+    assert_eq!(tree.len(), 7);
+  }
+
+  #[test]
+  fn within_test() {
+    let mut tree = tree();
+    let query = String::from("fghij");
+
+    assert_eq!(tree.within(&query, 1), vec![(0, &String::from("fghij")), (1, &String::from("fguij"))]);
+    assert_eq!(tree.within(&String::from("abcdx"), 2).len(), 1);
+
+    tree.remove(&query);
+    assert_eq!(tree.within(&query, 1), vec![(1, &String::from("fguij"))]);
+    assert_eq!(tree.within(&query, usize::MAX).len(), 6);
+  }
+
+  #[test]
+  fn nearest_test() {
+    let mut tree = tree();
+
+    assert_eq!(tree.nearest(&String::from("fgxij")), Some((1, &String::from("fghij"))));
+    assert_eq!(tree.nearest(&String::from("axcyz")), Some((1, &String::from("axcye"))));
+
+    tree.remove(&String::from("axcye"));
+    assert_eq!(tree.nearest(&String::from("axcyz")), Some((3, &String::from("abcde"))));
+    assert_eq!(BkTree::new().nearest(&String::from("abc")), None);
+  }
+}
+
+#[cfg(test)]
+mod proptests {
+  use super::*;
+  use proptest::prelude::*;
+
+  proptest! {
+    #[test]
+    fn queries_match_linear_scan(
+      ids in prop::collection::vec("[ab]{0,5}", 0..30),
+      removed in prop::collection::vec("[ab]{0,5}", 0..10),
+      query in "[ab]{0,5}",
+      k in 0..4_usize,
+    ) {
+      let mut tree: BkTree = ids.iter().cloned().collect();
+      let mut known: Vec<String> = ids.clone();
+
+      for id in removed.iter() {
+        tree.remove(id);
+        known.retain(|known| known != id);
+      }
+
+      known.sort();
+      known.dedup();
+
+      let mut scan: Vec<(usize, &String)> = known
+        .iter()
+        .map(|id| (distance(&query, id), id))
+        .collect();
+      scan.sort();
+
+      prop_assert_eq!(tree.len(), known.len());
+      prop_assert_eq!(tree.nearest(&query), scan.first().cloned());
+      prop_assert_eq!(
+        tree.within(&query, k),
+        scan.into_iter().filter(|(d, _)| *d <= k).collect::<Vec<(usize, &String)>>()
+      );
+    }
+  }
+}
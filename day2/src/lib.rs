@@ -1,20 +1,105 @@
 use std::fs::File;
-use std::io::{BufReader, BufRead, Error};
-use std::collections::HashMap;
+use std::io::{BufReader, BufRead};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
 
-pub fn read_input(filepath: &str) -> Result<Vec<String>, Error>{
-  let file = File::open(filepath)?;
-  let lines = BufReader::new(file)
+use unicode_segmentation::UnicodeSegmentation;
+
+use common::{Error, ParseError, Result, Rng, Solution};
+
+mod bk_tree;
+
+pub use bk_tree::{distance, distance_with, BkTree};
+
+/// Most ambiguous pairs spelled out in an error before the rest are elided.
+const LISTED_PAIRS: usize = 5;
+
+/// What counts as one character of a box id when comparing ids. By default
+/// every `char` is one character and case matters.
+///
+/// Neither option normalises: a precomposed letter and the same letter
+/// followed by a combining mark remain different characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IdOptions {
+  /// Treats every extended grapheme cluster, e.g. a letter together with
+  /// its combining marks, as a single character.
+  pub graphemes: bool,
+  /// Compares characters by their Unicode lowercase mapping.
+  pub fold_case: bool,
+}
+
+impl IdOptions {
+  /// Splits an id into its characters, as written.
+  pub fn segment<'a>(&self, id: &'a str) -> Vec<&'a str> {
+    match self.graphemes {
+      true => id.graphemes(true).collect(),
+      false => id.char_indices().map(|(i, c)| &id[i..i + c.len_utf8()]).collect(),
+    }
+  }
+
+  /// What a character is compared by.
+  pub fn key(&self, character: &str) -> String {
+    match self.fold_case {
+      true => character.to_lowercase(),
+      false => character.to_string(),
+    }
+  }
+
+  /// Whether two characters compare equal.
+  pub fn same(&self, a: &str, b: &str) -> bool {
+    match self.fold_case {
+      true => a.to_lowercase() == b.to_lowercase(),
+      false => a == b,
+    }
+  }
+}
+
+/// Checks that every id is as long as the first one.
+fn check_lengths(input: &Vec<String>, options: &IdOptions) -> std::result::Result<(), ParseError> {
+  let expected = match input.first() {
+    Some(id) => options.segment(id).len(),
+    None => return Ok(()),
+  };
+
+  for (i, id) in input.iter().enumerate() {
+    let characters = options.segment(id);
+    let found = characters.len();
+
+    if found != expected {
+      let column = characters.iter().take(expected).map(|c| c.chars().count()).sum::<usize>() + 1;
+      let message = format!("expected a box id of {} characters, found {}", expected, found);
+
+      return Err(ParseError::new(id, column, &message).at_line(i + 1));
+    }
+  }
+
+  Ok(())
+}
+
+/// Reads one box id per line. Lengths are left for `calc_part2_with` to
+/// check, as whether two ids are equally long depends on the `IdOptions`.
+pub fn read_from<R: BufRead>(reader: R) -> Result<Vec<String>> {
+  Ok(reader
     .lines()
-    .map(|line| line.unwrap())
-    .collect::<Vec<String>>();
+    .collect::<std::io::Result<Vec<String>>>()?)
+}
+
+pub fn parse_str(input: &str) -> Result<Vec<String>> {
+  read_from(input.as_bytes())
+}
 
-  Ok(lines)
+pub fn read_input(filepath: &str) -> Result<Vec<String>> {
+  read_from(BufReader::new(File::open(filepath)?))
 }
 
-fn count_char_ocurrences(input: &String) -> HashMap<char, usize> {
+fn count_ocurrences<T, I>(input: I) -> HashMap<T, usize>
+where
+  T: Eq + Hash,
+  I: IntoIterator<Item = T>,
+{
   input
-    .chars()
+    .into_iter()
     .fold(HashMap::new(), |mut map, chr| {
       let occurrences = map.entry(chr).or_insert(0);
       *occurrences += 1;
@@ -22,8 +107,8 @@ fn count_char_ocurrences(input: &String) -> HashMap<char, usize> {
     })
 }
 
-fn filter_unique_by_ocurrence_count(
-  input: HashMap<char, usize>, 
+fn filter_unique_by_ocurrence_count<T>(
+  input: HashMap<T, usize>, 
   whitelist: &Vec<usize>
 ) -> Vec<usize> {
   let mut v = input
@@ -40,60 +125,432 @@ fn filter_unique_by_ocurrence_count(
   v
 }
 
+/// How a `Checksum` folds its per-count tallies into a single value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combine {
+  Product,
+  Sum,
+  /// Keeps the tallies apart instead of folding them.
+  Breakdown,
+}
+
+/// Tallies behind a box id checksum: for every requested occurrence count,
+/// how many ids have some character occurring exactly that many times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checksum {
+  pub tallies: BTreeMap<usize, usize>,
+  pub combine: Combine,
+}
+
+impl Checksum {
+  /// The folded checksum; `None` for a breakdown or when no occurrence
+  /// counts were requested, as there is nothing to fold then.
+  pub fn value(&self) -> Option<usize> {
+    if self.tallies.is_empty() {
+      return None;
+    }
+
+    match self.combine {
+      Combine::Product => Some(self.tallies.values().product()),
+      Combine::Sum => Some(self.tallies.values().sum()),
+      Combine::Breakdown => None,
+    }
+  }
+}
+
+impl fmt::Display for Checksum {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.value() {
+      Some(value) => write!(f, "{}", value),
+      None => {
+        let tallies: Vec<String> = self.tallies
+          .iter()
+          .map(|(count, ids)| format!("{}: {}", count, ids))
+          .collect();
+        write!(f, "{}", tallies.join(", "))
+      },
+    }
+  }
+}
+
+/// Configures which occurrence counts a checksum tallies and how they are
+/// combined; the puzzle's checksum is the product of counts 2 and 3.
+#[derive(Debug, Clone)]
+pub struct ChecksumBuilder {
+  counts: Vec<usize>,
+  combine: Combine,
+  options: IdOptions,
+}
+
+impl Default for ChecksumBuilder {
+  fn default() -> ChecksumBuilder {
+    ChecksumBuilder { counts: vec![2, 3], combine: Combine::Product, options: IdOptions::default() }
+  }
+}
+
+impl ChecksumBuilder {
+  pub fn new() -> ChecksumBuilder {
+    ChecksumBuilder::default()
+  }
+
+  pub fn counts(mut self, counts: &[usize]) -> ChecksumBuilder {
+    self.counts = counts.to_vec();
+    self
+  }
+
+  pub fn combine(mut self, combine: Combine) -> ChecksumBuilder {
+    self.combine = combine;
+    self
+  }
+
+  pub fn options(mut self, options: IdOptions) -> ChecksumBuilder {
+    self.options = options;
+    self
+  }
+
+  pub fn build(&self, input: &Vec<String>) -> Checksum {
+    let tallies = input
+      .iter()
+      .map(|id| count_ocurrences(self.options
+        .segment(id)
+        .into_iter()
+        .map(|c| self.options.key(c))))
+      .map(|map| filter_unique_by_ocurrence_count(map, &self.counts))
+      .fold(
+        self.counts.iter().map(|&count| (count, 0)).collect(),
+        |mut tallies: BTreeMap<usize, usize>, v| {
+          for value in v {
+            *tallies.entry(value).or_insert(0) += 1;
+          }
+          tallies
+        },
+      );
+
+    Checksum { tallies, combine: self.combine }
+  }
+}
+
+/// Product of the ids with a character occurring exactly twice and those
+/// with one occurring exactly three times; 0 when either kind is missing.
 pub fn calc_part1(input: &Vec<String>) -> usize {
-  input
-    .iter()
-    .map(|box_id| count_char_ocurrences(box_id))
-    .map(|map| filter_unique_by_ocurrence_count(map, &vec![2, 3]))
-    .fold(HashMap::new(), |mut map, v| {
-      for value in v {
-        let count = map.entry(value).or_insert(0);
-        *count += 1
-      }
-      map
-    })
+  ChecksumBuilder::new().build(input).value().unwrap_or(0)
+}
+
+/// Number of positions at which two equally long ids differ, or `None` when
+/// their lengths differ.
+pub fn hamming_distance(a: &String, b: &String) -> Option<usize> {
+  hamming_distance_with(a, b, &IdOptions::default())
+}
+
+/// `hamming_distance` counting characters as `options` sees them.
+pub fn hamming_distance_with(a: &String, b: &String, options: &IdOptions) -> Option<usize> {
+  let (a, b) = (options.segment(a), options.segment(b));
+
+  if a.len() != b.len() {
+    return None;
+  }
+
+  Some(a
     .iter()
-    .fold(1, |a, (_, b)| a * b)
+    .zip(b.iter())
+    .map(|(x, y)| if options.same(x, y) { 0 } else { 1 })
+    .sum())
+}
+
+/// Characters two equally long ids share position by position, or `None`
+/// when their lengths differ.
+pub fn strip_unique_chars(a: &String, b: &String) -> Option<String> {
+  strip_unique_chars_within(a, b, Distance::Hamming(usize::MAX)).map(|(_, common)| common)
+}
+
+/// `strip_unique_chars` for ids lying `within` the given distance of each
+/// other, together with that distance, or `None` when they lie further
+/// apart. Under `Distance::Levenshtein` the common characters are those
+/// matched, rather than substituted, inserted or deleted, along one
+/// cheapest edit script; for ids differing in a single position that is
+/// exactly what `strip_unique_chars` keeps.
+pub fn strip_unique_chars_within(a: &String, b: &String, within: Distance) -> Option<(usize, String)> {
+  let a: Vec<char> = a.chars().collect();
+  let b: Vec<char> = b.chars().collect();
+  let (distance, matched) = common_positions(&a, &b, within)?;
+
+  Some((distance, matched.into_iter().map(|p| a[p]).collect()))
+}
+
+/// Distance between `a` and `b` as `within` measures it and the positions
+/// in `a` of the characters they share, or `None` when they lie further
+/// apart.
+fn common_positions(a: &[char], b: &[char], within: Distance) -> Option<(usize, Vec<usize>)> {
+  let (k, (distance, matched)) = match within {
+    Distance::Hamming(k) if a.len() == b.len() => {
+      let matched: Vec<usize> = (0..a.len()).filter(|&p| a[p] == b[p]).collect();
+      (k, (a.len() - matched.len(), matched))
+    },
+    Distance::Levenshtein(k) if a.len().abs_diff(b.len()) <= k => (k, align_positions(a, b)),
+    _ => return None,
+  };
+
+  match distance <= k {
+    true => Some((distance, matched)),
+    false => None,
+  }
+}
+
+/// Levenshtein distance between `a` and `b`, and the positions in `a` of
+/// the characters matched along one cheapest edit script.
+fn align_positions(a: &[char], b: &[char]) -> (usize, Vec<usize>) {
+  let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+  for (i, row) in d.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for (j, cell) in d[0].iter_mut().enumerate() {
+    *cell = j;
+  }
+
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+      d[i][j] = (d[i - 1][j - 1] + cost)
+        .min(d[i - 1][j] + 1)
+        .min(d[i][j - 1] + 1);
+    }
+  }
+
+  let (mut i, mut j) = (a.len(), b.len());
+  let mut matched: Vec<usize> = Vec::new();
+
+  while i > 0 && j > 0 {
+    if a[i - 1] == b[j - 1] && d[i][j] == d[i - 1][j - 1] {
+      matched.push(i - 1);
+      i -= 1;
+      j -= 1;
+    } else if d[i][j] == d[i - 1][j - 1] + 1 {
+      i -= 1;
+      j -= 1;
+    } else if d[i][j] == d[i - 1][j] + 1 {
+      i -= 1;
+    } else {
+      j -= 1;
+    }
+  }
+
+  matched.reverse();
+  (d[a.len()][b.len()], matched)
+}
+
+/// How far apart two ids may be for `find_similar` to pair them up, or for
+/// `strip_unique_chars_within` to compare them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distance {
+  /// At most `k` positions differ; ids of different lengths never match.
+  Hamming(usize),
+  /// At most `k` single character substitutions, insertions or deletions.
+  Levenshtein(usize),
 }
 
-fn hamming_distance(a: &String, b: &String) -> usize {
-  a
-    .chars()
-    .zip(b.chars())
-    .map(|(x, y)| if x == y { 0 } else { 1 })
-    .fold(0, |a, b| a + b)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Similar {
+  pub pair: (usize, usize),
+  pub distance: usize,
+  pub common: String,
+}
+
+/// Every pair of ids, as sorted `(i, j)` indices into `input`, that lies
+/// `within` the given distance of each other.
+pub fn find_similar(input: &Vec<String>, within: Distance) -> Vec<Similar> {
+  let ids: Vec<Vec<char>> = input.iter().map(|id| id.chars().collect()).collect();
+
+  similar_pairs(&ids, within)
+    .into_iter()
+    .map(|(pair, distance, matched)| Similar {
+      pair,
+      distance,
+      common: matched.into_iter().map(|p| ids[pair.0][p]).collect(),
+    })
+    .collect()
 }
 
-fn strip_unique_chars(a: &String, b: &String) -> String {
-  a
-    .chars()
-    .zip(b.chars())
-    .filter_map(|(x, y)| if x == y { Some(x) } else { None })
-    .fold(String::new(), |mut acc, chr| {
-      acc.push(chr);
-      acc
+/// `find_similar` comparing characters as `options` sees them, with the
+/// common characters taken as written in the first id of each pair.
+pub fn find_similar_with(input: &Vec<String>, within: Distance, options: &IdOptions) -> Result<Vec<Similar>> {
+  let ids: Vec<Vec<char>> = encode(input, options)?.iter().map(|id| id.chars().collect()).collect();
+
+  Ok(similar_pairs(&ids, within)
+    .into_iter()
+    .map(|(pair, distance, matched)| {
+      let characters = options.segment(&input[pair.0]);
+      Similar { pair, distance, common: matched.into_iter().map(|p| characters[p]).collect() }
     })
+    .collect())
+}
+
+/// The pairs `find_similar` reports, with the positions of the characters
+/// the first id of each pair shares with the second.
+fn similar_pairs(ids: &Vec<Vec<char>>, within: Distance) -> Vec<((usize, usize), usize, Vec<usize>)> {
+  (0..ids.len())
+    .flat_map(|i| (i + 1..ids.len()).map(move |j| (i, j)))
+    .filter_map(|(i, j)| common_positions(&ids[i], &ids[j], within)
+      .map(|(distance, matched)| ((i, j), distance, matched)))
+    .collect()
+}
+
+/// Finds every pair of equally long ids that differ in exactly one
+/// position, as sorted `(i, j)` indices into `input`.
+///
+/// Two such ids become equal once the differing character is deleted from
+/// both, so bucketing the ids by what is left after deleting each position
+/// in turn finds all pairs in O(n·L²) rather than comparing every pair.
+pub fn find_near_duplicates(input: &Vec<String>) -> Vec<(usize, usize)> {
+  let ids: Vec<Vec<char>> = input.iter().map(|id| id.chars().collect()).collect();
+  let width = ids.iter().map(|id| id.len()).max().unwrap_or(0);
+  let mut pairs: Vec<(usize, usize)> = Vec::new();
+
+  for position in 0..width {
+    let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+
+    for (i, id) in ids.iter().enumerate().filter(|(_, id)| id.len() > position) {
+      let key: String = id[..position].iter().chain(id[position + 1..].iter()).collect();
+      buckets.entry(key).or_default().push(i);
+    }
+
+    for bucket in buckets.values().filter(|bucket| bucket.len() > 1) {
+      for (k, &i) in bucket.iter().enumerate() {
+        pairs.extend(bucket[k + 1..]
+          .iter()
+          .filter(|&&j| ids[i][position] != ids[j][position])
+          .map(|&j| (i, j)));
+      }
+    }
+  }
+
+  pairs.sort_unstable();
+  pairs
+}
+
+/// `find_near_duplicates` comparing characters as `options` sees them.
+pub fn find_near_duplicates_with(input: &Vec<String>, options: &IdOptions) -> Result<Vec<(usize, usize)>> {
+  Ok(find_near_duplicates(&encode(input, options)?))
 }
 
-pub fn calc_part2(input: &Vec<String>) -> String {
-  let (a, b) = input
+/// Rewrites the ids so that every character, as `options` sees it, is a
+/// single `char`, which lets the char based searches compare them.
+fn encode(input: &Vec<String>, options: &IdOptions) -> Result<Vec<String>> {
+  let mut codes: HashMap<String, char> = HashMap::new();
+
+  input
     .iter()
-    .take(input.len() - 1)
-    .enumerate()
-    .flat_map(|(i, a)| {
-      input
+    .map(|id| options
+      .segment(id)
+      .into_iter()
+      .map(|c| {
+        let key = options.key(c);
+
+        if let Some(&code) = codes.get(&key) {
+          return Ok(code);
+        }
+
+        // Hand out scalar values in order, stepping over the surrogates.
+        let next = codes.len() as u32;
+        let code = char::from_u32(if next < 0xD800 { next } else { next + 0x800 })
+          .ok_or_else(|| Error::NoSolution("too many distinct characters".to_string()))?;
+
+        codes.insert(key, code);
+        Ok(code)
+      })
+      .collect())
+    .collect()
+}
+
+pub fn calc_part2(input: &Vec<String>) -> Result<String> {
+  calc_part2_with(input, &IdOptions::default())
+}
+
+/// Common letters of the only two ids differing in exactly one character,
+/// taken as written in the first of the two.
+pub fn calc_part2_with(input: &Vec<String>, options: &IdOptions) -> Result<String> {
+  if input.is_empty() {
+    return Err(Error::NoSolution("no box ids".to_string()));
+  }
+
+  check_lengths(input, options)?;
+
+  match find_near_duplicates_with(input, options)?.as_slice() {
+    [] => Err(Error::NoSolution("no box ids differ by exactly one character".to_string())),
+    [(i, j)] => Ok(options
+      .segment(&input[*i])
+      .into_iter()
+      .zip(options.segment(&input[*j]))
+      .filter(|(a, b)| options.same(a, b))
+      .map(|(a, _)| a)
+      .collect()),
+    pairs => {
+      let mut listed: Vec<String> = pairs
         .iter()
-        .skip(i + 1)
-        .filter_map(move |b| if hamming_distance(a, b) == 1 {
-          Some((a, b))
-        } else {
-          None
-        })
-    })
-    .last()
-    .unwrap();
+        .take(LISTED_PAIRS)
+        .map(|(i, j)| format!("lines {} and {}", i + 1, j + 1))
+        .collect();
+
+      if pairs.len() > LISTED_PAIRS {
+        listed.push("...".to_string());
+      }
 
-  strip_unique_chars(a, b)
+      Err(Error::Ambiguous(format!(
+        "{} pairs of box ids differ by exactly one character: {}",
+        pairs.len(),
+        listed.join(", ")
+      )))
+    },
+  }
+}
+
+/// Synthesises `size` box ids of 26 lowercase letters. Whenever there are
+/// at least two ids, exactly one pair of them differs by a single letter.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+  let random_id = |rng: &mut Rng| (0..26)
+    .map(|_| (b'a' + rng.below(26) as u8) as char)
+    .collect::<Vec<char>>();
+
+  let singles = if size >= 2 { size - 1 } else { size };
+  let mut ids: Vec<Vec<char>> = (0..singles)
+    .map(|_| random_id(&mut rng))
+    .collect();
+
+  if size >= 2 {
+    let mut twin = ids[0].clone();
+    let i = rng.below(26) as usize;
+    twin[i] = (b'a' + (twin[i] as u8 - b'a' + 1 + rng.below(25) as u8) % 26) as char;
+    ids.push(twin);
+  }
+
+  rng.shuffle(&mut ids);
+  ids
+    .into_iter()
+    .map(|id| id.into_iter().collect::<String>())
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+  const DAY: u8 = 2;
+
+  type Input = Vec<String>;
+  type Output = String;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    Ok(calc_part1(input).to_string())
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    calc_part2(input)
+  }
 }
 
 #[cfg(test)]
@@ -101,42 +558,42 @@ mod tests {
   use super::*;
 
   #[test]
-  fn count_char_ocurrences_test() {
+  fn count_ocurrences_test() {
     assert_eq!(
-      count_char_ocurrences(&String::from("abcdef")),
+      count_ocurrences(String::from("abcdef").chars()),
       [('a', 1), ('b', 1), ('c', 1), ('d', 1), ('e', 1), ('f', 1)]
         .iter().cloned().collect()
     );
 
     assert_eq!(
-      count_char_ocurrences(&String::from("bababc")),
+      count_ocurrences(String::from("bababc").chars()),
       [('b', 3), ('a', 2), ('c', 1)].iter().cloned().collect()
     );
 
     assert_eq!(
-      count_char_ocurrences(&String::from("abbcde")),
+      count_ocurrences(String::from("abbcde").chars()),
       [('a', 1), ('b', 2), ('c', 1), ('d', 1), ('e', 1)]
         .iter().cloned().collect()
     );
 
     assert_eq!(
-      count_char_ocurrences(&String::from("abcccd")),
+      count_ocurrences(String::from("abcccd").chars()),
       [('a', 1), ('b', 1), ('c', 3), ('d', 1)].iter().cloned().collect()
     );
 
     assert_eq!(
-      count_char_ocurrences(&String::from("aabcdd")),
+      count_ocurrences(String::from("aabcdd").chars()),
       [('a', 2), ('b', 1), ('c', 1), ('d', 2)].iter().cloned().collect()
     );
 
     assert_eq!(
-      count_char_ocurrences(&String::from("abcdee")),
+      count_ocurrences(String::from("abcdee").chars()),
       [('a', 1), ('b', 1), ('c', 1), ('d', 1), ('e', 2)]
         .iter().cloned().collect()
     );
 
     assert_eq!(
-      count_char_ocurrences(&String::from("ababab")),
+      count_ocurrences(String::from("ababab").chars()),
       [('a', 3), ('b', 3)].iter().cloned().collect()
     );
   }
@@ -219,55 +676,92 @@ mod tests {
     );
   }
 
+  #[test]
+  fn checksum_test() {
+    let input: Vec<String> = ["abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab"]
+      .iter()
+      .map(|id| id.to_string())
+      .collect();
+
+    let checksum = ChecksumBuilder::new().build(&input);
+    assert_eq!(checksum.tallies, [(2, 4), (3, 3)].iter().cloned().collect());
+    assert_eq!(checksum.value(), Some(12));
+
+    let sum = ChecksumBuilder::new().combine(Combine::Sum).build(&input);
+    assert_eq!(sum.value(), Some(7));
+
+    let breakdown = ChecksumBuilder::new()
+      .counts(&[1, 2, 3, 4])
+      .combine(Combine::Breakdown)
+      .build(&input);
+    assert_eq!(breakdown.value(), None);
+    assert_eq!(breakdown.to_string(), "1: 6, 2: 4, 3: 3, 4: 0".to_string());
+
+    assert_eq!(ChecksumBuilder::new().counts(&[4]).build(&input).value(), Some(0));
+    assert_eq!(ChecksumBuilder::new().counts(&[]).build(&input).value(), None);
+  }
+
+  #[test]
+  fn calc_part1_without_matches_test() {
+    assert_eq!(calc_part1(&vec![String::from("abcccd")]), 0);
+    assert_eq!(calc_part1(&vec![String::from("abcdef")]), 0);
+    assert_eq!(calc_part1(&vec![]), 0);
+  }
+
   #[test]
   fn hamming_distance_test() {
     assert_eq!(
       hamming_distance(&String::from("fghij"), &String::from("klmno")),
-      5
+      Some(5)
     );
 
     assert_eq!(
       hamming_distance(&String::from("karolin"), &String::from("kathrin")),
-      3
+      Some(3)
     );
 
     assert_eq!(
       hamming_distance(&String::from("karolin"), &String::from("kerstin")),
-      3
+      Some(3)
     );
 
     assert_eq!(
       hamming_distance(&String::from("1011101"), &String::from("1001001")),
-      2
+      Some(2)
     );
 
     assert_eq!(
       hamming_distance(&String::from("2173896"), &String::from("2233796")),
-      3
+      Some(3)
     );
+
+    assert_eq!(hamming_distance(&String::from("abc"), &String::from("abcd")), None);
+    assert_eq!(hamming_distance(&String::from("é"), &String::from("e\u{301}")), None);
   }
 
   #[test]
   fn strip_unique_chars_test() {
     assert_eq!(
-      strip_unique_chars(&String::from("fghij"), &String::from("fguij")), 
-      String::from("fgij")
+      strip_unique_chars(&String::from("fghij"), &String::from("fguij")),
+      Some(String::from("fgij"))
     );
 
     assert_eq!(
-      strip_unique_chars(&String::from("0011001"), &String::from("1011001")), 
-      String::from("011001")
+      strip_unique_chars(&String::from("0011001"), &String::from("1011001")),
+      Some(String::from("011001"))
     );
 
     assert_eq!(
-      strip_unique_chars(&String::from(""), &String::from("")), 
-      String::from("")
+      strip_unique_chars(&String::from(""), &String::from("")),
+      Some(String::from(""))
     );
 
     assert_eq!(
-      strip_unique_chars(&String::from("a"), &String::from("b")), 
-      String::from("")
+      strip_unique_chars(&String::from("a"), &String::from("b")),
+      Some(String::from(""))
     );
+
+    assert_eq!(strip_unique_chars(&String::from("abcd"), &String::from("abc")), None);
   }
 
   #[test]
@@ -281,7 +775,7 @@ mod tests {
         String::from("fguij"),
         String::from("axcye"),
         String::from("wvxyz"),
-      ]),
+      ]).unwrap(),
       String::from("fgij")
     );
 
@@ -294,7 +788,7 @@ mod tests {
         String::from("pqrst"),
         String::from("axcye"),
         String::from("wvxyz"),
-      ]),
+      ]).unwrap(),
       String::from("fgij")
     );
 
@@ -307,7 +801,7 @@ mod tests {
         String::from("wvxyz"),
         String::from("fghij"),
         String::from("fguij"),
-      ]),
+      ]).unwrap(),
       String::from("fgij")
     );
 
@@ -320,8 +814,300 @@ mod tests {
         String::from("axcye"),
         String::from("wvxyz"),
         String::from("fguij"),
-      ]),
+      ]).unwrap(),
       String::from("fgij")
     );
+
+    assert!(calc_part2(&vec![]).is_err());
+    assert!(calc_part2(&vec![String::from("abcde")]).is_err());
+    assert!(calc_part2(&vec![String::from("abcde"), String::from("fghij")]).is_err());
+  }
+
+  #[test]
+  fn calc_part2_errors_test() {
+    match calc_part2(&vec![]) {
+      Err(Error::NoSolution(reason)) => assert_eq!(reason, "no box ids".to_string()),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    match calc_part2(&vec![String::from("abcde"), String::from("fghij")]) {
+      Err(Error::NoSolution(_)) => (),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    match calc_part2(&vec![String::from("abcde"), String::from("abcdx"), String::from("xbcdx")]) {
+      Err(Error::Ambiguous(reason)) => assert_eq!(
+        reason,
+        "2 pairs of box ids differ by exactly one character: lines 1 and 2, lines 2 and 3".to_string()
+      ),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    let many: Vec<String> = (b'a'..=b'h').map(|c| format!("ab{}", c as char)).collect();
+    match calc_part2(&many) {
+      Err(Error::Ambiguous(reason)) => assert!(reason.starts_with("28 pairs") && reason.ends_with(", ...")),
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    match calc_part2(&vec![String::from("abcde"), String::from("abcd")]) {
+      Err(Error::Parse(e)) => assert_eq!((e.line, e.column), (2, 5)),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn id_options_test() {
+    let graphemes = IdOptions { graphemes: true, ..IdOptions::default() };
+    let fold_case = IdOptions { fold_case: true, ..IdOptions::default() };
+
+    assert_eq!(IdOptions::default().segment("ae\u{301}"), vec!["a", "e", "\u{301}"]);
+    assert_eq!(graphemes.segment("ae\u{301}"), vec!["a", "e\u{301}"]);
+    assert_eq!(fold_case.key("Ä"), "ä".to_string());
+    assert_eq!(IdOptions::default().key("Ä"), "Ä".to_string());
+  }
+
+  #[test]
+  fn calc_part2_with_test() {
+    let marks = vec![String::from("xae\u{301}z"), String::from("xae\u{300}z")];
+    let graphemes = IdOptions { graphemes: true, ..IdOptions::default() };
+
+    assert_eq!(calc_part2(&marks).unwrap(), String::from("xaez"));
+    assert_eq!(calc_part2_with(&marks, &graphemes).unwrap(), String::from("xaz"));
+
+    let cases = vec![String::from("AbCd"), String::from("abcX"), String::from("abce")];
+    let fold_case = IdOptions { fold_case: true, ..IdOptions::default() };
+
+    assert_eq!(calc_part2(&cases).unwrap(), String::from("abc"));
+    match calc_part2_with(&cases, &fold_case) {
+      Err(Error::Ambiguous(_)) => (),
+      r => panic!("unexpected result {:?}", r),
+    }
+    assert_eq!(
+      calc_part2_with(&vec![String::from("ÄbC"), String::from("äbd")], &fold_case).unwrap(),
+      String::from("Äb")
+    );
+
+    match calc_part2_with(&vec![String::from("ae\u{301}"), String::from("abc")], &graphemes) {
+      Err(Error::Parse(e)) => assert_eq!((e.line, e.column), (2, 3)),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  #[test]
+  fn checksum_options_test() {
+    let input = vec![String::from("e\u{301}e\u{301}x"), String::from("aAa")];
+
+    assert_eq!(ChecksumBuilder::new().build(&input).tallies, [(2, 2), (3, 0)].iter().cloned().collect());
+    assert_eq!(
+      ChecksumBuilder::new()
+        .options(IdOptions { graphemes: true, fold_case: true })
+        .build(&input)
+        .tallies,
+      [(2, 1), (3, 1)].iter().cloned().collect()
+    );
+  }
+
+  #[test]
+  fn read_from_lengths_test() {
+    assert_eq!(parse_str("abc\nabd\nabcd\n").unwrap().len(), 3);
+
+    match calc_part2(&parse_str("abc\nabd\nabcd\n").unwrap()) {
+      Err(Error::Parse(e)) => {
+        assert_eq!((e.line, e.column), (3, 4));
+        assert_eq!(e.message, "expected a box id of 3 characters, found 4".to_string());
+      },
+      r => panic!("unexpected result {:?}", r),
+    }
+
+    let marks = parse_str("xae\u{301}z\nxaez\nxbez").unwrap();
+    let graphemes = IdOptions { graphemes: true, ..IdOptions::default() };
+
+    assert!(matches!(calc_part2(&marks), Err(Error::Parse(_))));
+    assert!(matches!(calc_part2_with(&marks, &graphemes), Err(Error::Ambiguous(_))));
+    assert_eq!(calc_part2_with(&marks[..2].to_vec(), &graphemes).unwrap(), String::from("xaz"));
+  }
+
+  #[test]
+  fn options_test() {
+    let marks = vec![String::from("xae\u{301}z"), String::from("xaez"), String::from("xbez")];
+    let graphemes = IdOptions { graphemes: true, ..IdOptions::default() };
+    let fold_case = IdOptions { fold_case: true, ..IdOptions::default() };
+
+    assert_eq!(hamming_distance(&marks[0], &marks[1]), None);
+    assert_eq!(hamming_distance_with(&marks[0], &marks[1], &graphemes), Some(1));
+    assert_eq!(hamming_distance_with(&String::from("AbC"), &String::from("abd"), &fold_case), Some(1));
+
+    assert_eq!(find_near_duplicates(&marks), vec![(1, 2)]);
+    assert_eq!(find_near_duplicates_with(&marks, &graphemes).unwrap(), vec![(0, 1), (1, 2)]);
+
+    let cases = vec![String::from("AB-1234"), String::from("ab-1243"), String::from("ab-124")];
+    assert!(find_similar(&cases, Distance::Hamming(2)).is_empty());
+    assert_eq!(find_similar_with(&cases, Distance::Hamming(2), &fold_case).unwrap(), vec![
+      Similar { pair: (0, 1), distance: 2, common: String::from("AB-12") },
+    ]);
+    assert_eq!(find_similar_with(&marks, Distance::Levenshtein(1), &graphemes).unwrap(), vec![
+      Similar { pair: (0, 1), distance: 1, common: String::from("xaz") },
+      Similar { pair: (1, 2), distance: 1, common: String::from("xez") },
+    ]);
+  }
+
+  #[test]
+  fn find_near_duplicates_test() {
+    let input = vec![
+      String::from("abcde"),
+      String::from("abxde"),
+      String::from("abcdf"),
+      String::from("abcde"),
+      String::from("abcd"),
+      String::from("zbxde"),
+    ];
+
+    assert_eq!(
+      find_near_duplicates(&input),
+      vec![(0, 1), (0, 2), (1, 3), (1, 5), (2, 3)]
+    );
+    assert!(find_near_duplicates(&vec![]).is_empty());
+  }
+
+  #[test]
+  fn strip_unique_chars_within_test() {
+    let levenshtein = |a: &str, b: &str| {
+      strip_unique_chars_within(&String::from(a), &String::from(b), Distance::Levenshtein(usize::MAX))
+    };
+
+    assert_eq!(levenshtein("kitten", "sitting"), Some((3, String::from("ittn"))));
+    assert_eq!(levenshtein("fghij", "fguij"), Some((1, String::from("fgij"))));
+    assert_eq!(levenshtein("abcde", "bcdef"), Some((2, String::from("bcde"))));
+    assert_eq!(levenshtein("", "abc"), Some((3, String::from(""))));
+
+    let (kitten, sitting) = (String::from("kitten"), String::from("sitting"));
+    assert_eq!(strip_unique_chars_within(&kitten, &sitting, Distance::Levenshtein(2)), None);
+    assert_eq!(strip_unique_chars_within(&kitten, &sitting, Distance::Hamming(usize::MAX)), None);
+    assert_eq!(
+      strip_unique_chars_within(&String::from("fghij"), &String::from("fguij"), Distance::Hamming(1)),
+      Some((1, String::from("fgij")))
+    );
+    assert_eq!(
+      strip_unique_chars_within(&String::from("fghij"), &String::from("fguij"), Distance::Hamming(0)),
+      None
+    );
+  }
+
+  #[test]
+  fn find_similar_test() {
+    let input = vec![
+      String::from("AB-1234"),
+      String::from("AB-1243"),
+      String::from("AB-124"),
+      String::from("XY-9999"),
+    ];
+
+    assert_eq!(find_similar(&input, Distance::Hamming(2)), vec![
+      Similar { pair: (0, 1), distance: 2, common: String::from("AB-12") },
+    ]);
+    assert!(find_similar(&input, Distance::Hamming(1)).is_empty());
+
+    assert_eq!(find_similar(&input, Distance::Levenshtein(1)), vec![
+      Similar { pair: (0, 2), distance: 1, common: String::from("AB-124") },
+      Similar { pair: (1, 2), distance: 1, common: String::from("AB-124") },
+    ]);
+    assert_eq!(
+      find_similar(&input, Distance::Levenshtein(2))
+        .iter()
+        .map(|similar| similar.pair)
+        .collect::<Vec<(usize, usize)>>(),
+      vec![(0, 1), (0, 2), (1, 2)]
+    );
+  }
+
+  #[test]
+  fn generate_test() {
+    assert_eq!(generate(500, 3), generate(500, 3));
+    assert_eq!(parse_str(&generate(0, 3)).unwrap().len(), 0);
+    assert_eq!(parse_str(&generate(1, 3)).unwrap().len(), 1);
+
+    let input = parse_str(&generate(500, 3)).unwrap();
+    assert_eq!(input.len(), 500);
+    assert!(input.iter().all(|id| id.len() == 26));
+    assert_eq!(calc_part2(&input).unwrap().len(), 25);
+  }
+}
+
+#[cfg(test)]
+mod proptests {
+  use super::*;
+  use proptest::prelude::*;
+
+  fn ids(len: usize) -> impl Strategy<Value = (String, String, String)> {
+    let id = move || prop::collection::vec(prop::sample::select(vec!['a', 'b', 'c']), len)
+      .prop_map(|v| v.into_iter().collect::<String>());
+
+    (id(), id(), id())
+  }
+
+  proptest! {
+    #[test]
+    fn hamming_distance_is_a_metric((a, b, c) in (0..12_usize).prop_flat_map(ids)) {
+      let distance = |x: &String, y: &String| hamming_distance(x, y).unwrap();
+
+      prop_assert_eq!(distance(&a, &a), 0);
+      prop_assert_eq!(distance(&a, &b) == 0, a == b);
+      prop_assert_eq!(distance(&a, &b), distance(&b, &a));
+      prop_assert!(distance(&a, &c) <= distance(&a, &b) + distance(&b, &c));
+    }
+
+    #[test]
+    fn strip_unique_chars_drops_exactly_the_mismatches((a, b, _) in (0..12_usize).prop_flat_map(ids)) {
+      let common = strip_unique_chars(&a, &b).unwrap();
+      prop_assert_eq!(common.len() + hamming_distance(&a, &b).unwrap(), a.len());
+      prop_assert_eq!(Some(common), strip_unique_chars(&b, &a));
+    }
+
+    #[test]
+    fn find_near_duplicates_matches_pairwise_scan(
+      input in (0..6_usize).prop_flat_map(|len| prop::collection::vec(
+        prop::collection::vec(prop::sample::select(vec!['a', 'b']), len)
+          .prop_map(|v| v.into_iter().collect::<String>()),
+        0..20,
+      ))
+    ) {
+      let pairwise: Vec<(usize, usize)> = (0..input.len())
+        .flat_map(|i| (i + 1..input.len()).map(move |j| (i, j)))
+        .filter(|&(i, j)| hamming_distance(&input[i], &input[j]) == Some(1))
+        .collect();
+
+      prop_assert_eq!(find_near_duplicates(&input), pairwise);
+    }
+
+    #[test]
+    fn levenshtein_distance_is_a_metric(
+      (a, b, c) in ("[ab]{0,6}", "[ab]{0,6}", "[ab]{0,6}")
+    ) {
+      let distance = |x: &String, y: &String| strip_unique_chars_within(x, y, Distance::Levenshtein(usize::MAX))
+        .unwrap()
+        .0;
+
+      prop_assert_eq!(distance(&a, &a), 0);
+      prop_assert_eq!(distance(&a, &b) == 0, a == b);
+      prop_assert_eq!(distance(&a, &b), distance(&b, &a));
+      prop_assert!(distance(&a, &c) <= distance(&a, &b) + distance(&b, &c));
+    }
+
+    #[test]
+    fn levenshtein_never_exceeds_hamming((a, b, _) in (0..12_usize).prop_flat_map(ids)) {
+      let (distance, common) = strip_unique_chars_within(&a, &b, Distance::Levenshtein(usize::MAX)).unwrap();
+
+      prop_assert!(distance <= hamming_distance(&a, &b).unwrap());
+      prop_assert!(common.len() >= a.len() - distance);
+    }
+
+    #[test]
+    fn calc_part1_matches_oracle(input in prop::collection::vec("[a-d]{0,8}", 0..30)) {
+      let count = |n: usize| input
+        .iter()
+        .filter(|id| id.chars().any(|c| id.chars().filter(|&x| x == c).count() == n))
+        .count();
+      prop_assert_eq!(calc_part1(&input), count(2) * count(3));
+    }
   }
 }
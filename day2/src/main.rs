@@ -1,11 +1,17 @@
-use day2::read_input;
+use std::env;
+
+use common::{Result, Source};
+
+use day2::read_from;
 use day2::calc_part1;
 use day2::calc_part2;
 
-fn main() {
-  let filepath = "./input.txt";
-  let input = read_input(&filepath).unwrap();
+fn main() -> Result<()> {
+  let source = Source::from_arg(&env::args().nth(1).unwrap_or_else(|| "./input.txt".to_string()));
+  let input = read_from(source.reader()?)?;
 
   println!("Day #2, part #1 {:?}", calc_part1(&input));
-  println!("Day #2, part #2 {:?}", calc_part2(&input));
+  println!("Day #2, part #2 {:?}", calc_part2(&input)?);
+
+  Ok(())
 }
\ No newline at end of file
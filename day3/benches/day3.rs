@@ -0,0 +1,55 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::Solution;
+use day3::Day3;
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// Tiles the fabric horizontally: copy `k` of every claim is moved 1000
+/// inches to the right of copy `k - 1` and gets a fresh id.
+fn scale(factor: usize) -> String {
+  let claims = INPUT.lines().count();
+
+  (0..factor)
+    .flat_map(|k| INPUT.lines().map(move |line| {
+      let fields: Vec<usize> = line
+        .split(|c| " #@,:x".contains(c))
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().unwrap())
+        .collect();
+
+      format!(
+        "#{} @ {},{}: {}x{}",
+        fields[0] + k * claims, fields[1] + k * 1000, fields[2], fields[3], fields[4]
+      )
+    }))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day3");
+  group.sample_size(10);
+
+  for factor in [1, 4, 16].iter() {
+    let text = scale(*factor);
+    let input = Day3::parse_str(&text).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", factor), &text, |b, text| {
+      b.iter(|| Day3::parse_str(black_box(text)))
+    });
+    group.bench_with_input(BenchmarkId::new("part1", factor), &input, |b, input| {
+      b.iter(|| Day3::part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", factor), &input, |b, input| {
+      b.iter(|| Day3::part2(black_box(input)))
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
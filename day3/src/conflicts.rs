@@ -0,0 +1,146 @@
+use serde_json::json;
+
+use common::{Error, Result};
+
+use crate::{Claim, ClaimIndex};
+
+/// Another claim colliding with a claim, and how many square inches of
+/// fabric the two of them share.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conflict {
+  pub id: usize,
+  pub shared: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimConflicts {
+  pub id: usize,
+  pub conflicts: Vec<Conflict>,
+}
+
+/// Which claims collide with which, listing every claim in input order and
+/// every collision from both sides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlapGraph {
+  pub claims: Vec<ClaimConflicts>,
+}
+
+impl OverlapGraph {
+  /// Ids of the claims that collide with no other claim.
+  pub fn intact(&self) -> Vec<usize> {
+    self.claims
+      .iter()
+      .filter(|claim| claim.conflicts.is_empty())
+      .map(|claim| claim.id)
+      .collect()
+  }
+
+  /// One `claim,other,shared` row per collision, seen from each side.
+  pub fn to_csv(&self) -> String {
+    self.claims
+      .iter()
+      .flat_map(|claim| claim.conflicts
+        .iter()
+        .map(move |conflict| format!("{},{},{}\n", claim.id, conflict.id, conflict.shared)))
+      .fold("claim,other,shared\n".to_string(), |mut csv, row| {
+        csv.push_str(&row);
+        csv
+      })
+  }
+
+  pub fn to_json(&self) -> String {
+    let claims: Vec<serde_json::Value> = self.claims
+      .iter()
+      .map(|claim| json!({
+        "id": claim.id,
+        "conflicts": claim.conflicts
+          .iter()
+          .map(|conflict| json!({ "id": conflict.id, "shared": conflict.shared }))
+          .collect::<Vec<serde_json::Value>>(),
+      }))
+      .collect();
+
+    json!(claims).to_string()
+  }
+}
+
+/// Fails with an overflow when two claims share more square inches than a
+/// `usize` counts.
+pub fn calc_overlap_graph(claims: &Vec<Claim>) -> Result<OverlapGraph> {
+  let index = ClaimIndex::new(claims);
+
+  Ok(OverlapGraph {
+    claims: claims
+      .iter()
+      .map(|claim| Ok(ClaimConflicts {
+        id: claim.id,
+        conflicts: index
+          .overlapping(claim)
+          .into_iter()
+          .map(|other| {
+            let shared = claim.rect().shared_area(&other.rect()).ok_or_else(|| Error::Overflow(format!(
+              "claims #{} and #{} share too many square inches to count",
+              claim.id, other.id
+            )))?;
+
+            Ok(Conflict { id: other.id, shared })
+          })
+          .collect::<Result<Vec<Conflict>>>()?,
+      }))
+      .collect::<Result<Vec<ClaimConflicts>>>()?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn graph() -> OverlapGraph {
+    let mut claims = crate::tests::example();
+    claims.extend(crate::parse_str("#4 @ 2,4: 2x1").unwrap());
+    calc_overlap_graph(&claims).unwrap()
+  }
+
+  #[test]
+  fn calc_overlap_graph_test() {
+    let graph = graph();
+
+    assert_eq!(graph.claims[0], ClaimConflicts {
+      id: 1,
+      conflicts: vec![Conflict { id: 2, shared: 4 }, Conflict { id: 4, shared: 2 }],
+    });
+    assert_eq!(graph.claims[1].conflicts, vec![Conflict { id: 1, shared: 4 }, Conflict { id: 4, shared: 1 }]);
+    assert_eq!(graph.intact(), vec![3]);
+  }
+
+  #[test]
+  fn export_test() {
+    let graph = graph();
+
+    assert_eq!(
+      graph.to_csv(),
+      "claim,other,shared\n1,2,4\n1,4,2\n2,1,4\n2,4,1\n4,1,2\n4,2,1\n".to_string()
+    );
+    assert!(graph.to_json().starts_with(
+      "[{\"id\":1,\"conflicts\":[{\"id\":2,\"shared\":4},{\"id\":4,\"shared\":2}]},"
+    ));
+    assert!(graph.to_json().contains("{\"id\":3,\"conflicts\":[]}"));
+    assert_eq!(calc_overlap_graph(&vec![]).unwrap().to_json(), "[]".to_string());
+  }
+
+  #[test]
+  fn empty_claim_test() {
+    let claims = crate::parse_str("#1 @ 0,0: 5x5\n#2 @ 2,2: 0x0").unwrap();
+    let graph = calc_overlap_graph(&claims).unwrap();
+
+    assert_eq!(graph.to_csv(), "claim,other,shared\n".to_string());
+    assert_eq!(graph.intact(), vec![1, 2]);
+    assert_eq!(crate::calc_part2(&claims).unwrap(), 1);
+  }
+
+  #[test]
+  fn overflowing_shared_area_test() {
+    let claims = crate::parse_str("#1 @ 0,0: 4294967296x4294967296\n#2 @ 0,0: 4294967296x4294967296").unwrap();
+    assert!(matches!(calc_overlap_graph(&claims), Err(Error::Overflow(_))));
+  }
+}
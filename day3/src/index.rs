@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use crate::{collide, Claim};
+
+/// A region of fabric `len_x` by `len_y` inches, starting `x` inches from
+/// the left edge and `y` inches from the top edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+  pub x: usize,
+  pub y: usize,
+  pub len_x: usize,
+  pub len_y: usize,
+}
+
+impl Rect {
+  /// First column past the rectangle, saturating at the largest coordinate.
+  fn end_x(&self) -> usize {
+    self.x.saturating_add(self.len_x)
+  }
+
+  /// First row past the rectangle, saturating at the largest coordinate.
+  fn end_y(&self) -> usize {
+    self.y.saturating_add(self.len_y)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len_x == 0 || self.len_y == 0
+  }
+
+  /// Whether the rectangles share some fabric; an empty rectangle shares
+  /// none with anything.
+  pub fn intersects(&self, other: &Rect) -> bool {
+    !self.is_empty()
+      && !other.is_empty()
+      && self.x < other.end_x()
+      && self.end_x() > other.x
+      && self.y < other.end_y()
+      && self.end_y() > other.y
+  }
+
+  /// Square inches lying in both rectangles, or `None` when there are too
+  /// many to count in a `usize`.
+  pub fn shared_area(&self, other: &Rect) -> Option<usize> {
+    let shared_x = self.end_x().min(other.end_x()).saturating_sub(self.x.max(other.x));
+    let shared_y = self.end_y().min(other.end_y()).saturating_sub(self.y.max(other.y));
+
+    shared_x.checked_mul(shared_y)
+  }
+}
+
+/// Most grid cells a claim is filed under; larger claims are kept aside and
+/// checked by every query instead.
+const MAX_CELLS: usize = 64;
+
+/// Uniform grid over the claims: every claim is filed under each grid cell
+/// it touches, so a query only looks at the claims sharing a cell with it.
+/// Cells are stored sparsely and sized after the median claim, and a query
+/// never visits more cells than are stored.
+pub struct ClaimIndex<'a> {
+  claims: &'a Vec<Claim>,
+  cell: usize,
+  cells: HashMap<(usize, usize), Vec<usize>>,
+  /// Smallest and largest cell coordinates in use, as `(x, y)` pairs.
+  bounds: Option<((usize, usize), (usize, usize))>,
+  large: Vec<usize>,
+}
+
+/// Inclusive ranges of grid cell columns and rows.
+type Span = ((usize, usize), (usize, usize));
+
+impl<'a> ClaimIndex<'a> {
+  pub fn new(claims: &'a Vec<Claim>) -> ClaimIndex<'a> {
+    let mut sides: Vec<usize> = claims.iter().map(|c| c.len_x.max(c.len_y)).collect();
+    sides.sort_unstable();
+
+    let cell = sides.get(sides.len() / 2).cloned().unwrap_or(1).max(1);
+    let mut index = ClaimIndex { claims, cell, cells: HashMap::new(), bounds: None, large: vec![] };
+
+    for (i, claim) in claims.iter().enumerate() {
+      let ((from_x, to_x), (from_y, to_y)) = index.span(&claim.rect());
+
+      if (to_x - from_x + 1).saturating_mul(to_y - from_y + 1) > MAX_CELLS {
+        index.large.push(i);
+        continue;
+      }
+
+      for x in from_x..=to_x {
+        for y in from_y..=to_y {
+          index.cells.entry((x, y)).or_default().push(i);
+        }
+      }
+
+      index.bounds = Some(match index.bounds {
+        None => ((from_x, from_y), (to_x, to_y)),
+        Some((low, high)) => ((low.0.min(from_x), low.1.min(from_y)), (high.0.max(to_x), high.1.max(to_y))),
+      });
+    }
+
+    index
+  }
+
+  /// Grid cells touched by `rect`, counting an empty rectangle as touching
+  /// the cell it starts in.
+  fn span(&self, rect: &Rect) -> Span {
+    let last = |start: usize, len: usize| start.saturating_add(len.max(1) - 1) / self.cell;
+
+    ((rect.x / self.cell, last(rect.x, rect.len_x)), (rect.y / self.cell, last(rect.y, rect.len_y)))
+  }
+
+  /// Claims filed under any cell `rect` touches or kept aside as large, in
+  /// input order.
+  fn candidates(&self, rect: &Rect) -> Vec<&'a Claim> {
+    let mut candidates: Vec<usize> = self.large.clone();
+
+    if let Some(((low_x, low_y), (high_x, high_y))) = self.bounds {
+      let ((from_x, to_x), (from_y, to_y)) = self.span(rect);
+      let (from_x, to_x) = (from_x.max(low_x), to_x.min(high_x));
+      let (from_y, to_y) = (from_y.max(low_y), to_y.min(high_y));
+
+      if from_x <= to_x && from_y <= to_y {
+        let touched = (to_x - from_x + 1).saturating_mul(to_y - from_y + 1);
+
+        if touched > self.cells.len() {
+          candidates.extend(self.cells
+            .iter()
+            .filter(|((x, y), _)| (from_x..=to_x).contains(x) && (from_y..=to_y).contains(y))
+            .flat_map(|(_, filed)| filed.iter().cloned()));
+        } else {
+          candidates.extend((from_x..=to_x)
+            .flat_map(|x| (from_y..=to_y).map(move |y| (x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .cloned());
+        }
+      }
+    }
+
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    candidates.into_iter().map(|i| &self.claims[i]).collect()
+  }
+
+  /// Claims sharing some fabric with `region`, in input order.
+  pub fn in_region(&self, region: &Rect) -> Vec<&'a Claim> {
+    self
+      .candidates(region)
+      .into_iter()
+      .filter(|claim| claim.rect().intersects(region))
+      .collect()
+  }
+
+  /// Claims covering the square inch at (`x`, `y`).
+  pub fn at_point(&self, x: usize, y: usize) -> Vec<&'a Claim> {
+    self.in_region(&Rect { x, y, len_x: 1, len_y: 1 })
+  }
+
+  /// Claims other than `claim` that it collides with.
+  pub fn overlapping(&self, claim: &Claim) -> Vec<&'a Claim> {
+    self
+      .candidates(&claim.rect())
+      .into_iter()
+      .filter(|other| *other != claim && collide(other, claim))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn claims() -> Vec<Claim> {
+    let mut claims = crate::tests::example();
+    claims.extend(crate::parse_str("#4 @ 100,100: 1x1").unwrap());
+    claims
+  }
+
+  fn ids(claims: Vec<&Claim>) -> Vec<usize> {
+    claims.iter().map(|c| c.id()).collect()
+  }
+
+  #[test]
+  fn intersects_test() {
+    let a = Rect { x: 0, y: 0, len_x: 2, len_y: 2 };
+
+    assert!(a.intersects(&Rect { x: 1, y: 1, len_x: 2, len_y: 2 }));
+    assert!(!a.intersects(&Rect { x: 2, y: 0, len_x: 2, len_y: 2 }));
+    assert!(!a.intersects(&Rect { x: 0, y: 2, len_x: 2, len_y: 2 }));
+    assert!(!a.intersects(&Rect { x: 1, y: 1, len_x: 0, len_y: 0 }));
+    assert!(!Rect { x: 1, y: 1, len_x: 1, len_y: 0 }.intersects(&a));
+  }
+
+  #[test]
+  fn shared_area_test() {
+    let a = Rect { x: 0, y: 0, len_x: 4, len_y: 3 };
+
+    assert_eq!(a.shared_area(&Rect { x: 1, y: 1, len_x: 10, len_y: 10 }), Some(6));
+    assert_eq!(a.shared_area(&Rect { x: 1, y: 1, len_x: 1, len_y: 1 }), Some(1));
+    assert_eq!(a.shared_area(&Rect { x: 4, y: 0, len_x: 2, len_y: 2 }), Some(0));
+
+    let huge = Rect { x: 0, y: 0, len_x: 1 << 32, len_y: 1 << 32 };
+    assert_eq!(huge.shared_area(&huge), None);
+  }
+
+  #[test]
+  fn queries_test() {
+    let claims = claims();
+    let index = ClaimIndex::new(&claims);
+
+    assert_eq!(ids(index.overlapping(&claims[0])), vec![2]);
+    assert_eq!(ids(index.overlapping(&claims[1])), vec![1]);
+    assert!(index.overlapping(&claims[2]).is_empty());
+
+    assert_eq!(ids(index.at_point(3, 3)), vec![1, 2]);
+    assert_eq!(ids(index.at_point(6, 6)), vec![3]);
+    assert_eq!(ids(index.at_point(100, 100)), vec![4]);
+    assert!(index.at_point(0, 0).is_empty());
+
+    assert_eq!(ids(index.in_region(&Rect { x: 4, y: 4, len_x: 100, len_y: 100 })), vec![1, 2, 3, 4]);
+    assert!(index.in_region(&Rect { x: 10, y: 10, len_x: 50, len_y: 50 }).is_empty());
+  }
+
+  #[test]
+  fn huge_queries_test() {
+    let mut claims = claims();
+    claims.extend(crate::parse_str("#5 @ 50,0: 1000000000x1000000000").unwrap());
+    let index = ClaimIndex::new(&claims);
+
+    let everything = Rect { x: 0, y: 0, len_x: 1_000_000, len_y: 1_000_000 };
+    assert_eq!(ids(index.in_region(&everything)), vec![1, 2, 3, 4, 5]);
+
+    assert!(index.in_region(&Rect { x: usize::MAX - 1, y: 0, len_x: usize::MAX, len_y: 5 }).is_empty());
+    assert_eq!(ids(index.in_region(&Rect { x: 60, y: 0, len_x: usize::MAX, len_y: usize::MAX })), vec![4, 5]);
+
+    assert_eq!(ids(index.overlapping(&claims[4])), vec![4]);
+    assert_eq!(ids(index.at_point(100, 100)), vec![4, 5]);
+    assert_eq!(ids(index.at_point(3, 3)), vec![1, 2]);
+  }
+}
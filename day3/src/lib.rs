@@ -1,8 +1,18 @@
 use std::fs::File;
 use std::io::{BufReader, BufRead};
-use std::cmp::max;
-use std::collections::HashMap;
-use regex::Regex;
+use std::cmp::{max, min};
+
+use common::{parse_lines, Cursor, Error, ParseError, Result, Rng, Solution};
+
+mod conflicts;
+mod index;
+mod render;
+mod sweep;
+
+pub use conflicts::{calc_overlap_graph, ClaimConflicts, Conflict, OverlapGraph};
+pub use index::{ClaimIndex, Rect};
+pub use render::{render_ascii, render_ppm, MAX_ASCII_SIDE, MAX_PIXELS};
+pub use sweep::overlap_area;
 
 #[derive(Debug, PartialEq)]
 pub struct Claim {
@@ -13,105 +23,188 @@ pub struct Claim {
   len_y: usize,
 }
 
-fn parse(input: &String) -> Result<Claim, String> {
-  let re = Regex::new(r"#(\d+)\s@\s(\d+),(\d+):\s(\d+)x(\d+)")
-    .map_err(|e| e.to_string())?;
-
-  let caps = re
-    .captures(input)
-    .ok_or("could not match input")?;
+impl Claim {
+  pub fn id(&self) -> usize {
+    self.id
+  }
 
-  let matches = caps
-    .iter()
-    .skip(1)
-    .map(|o| o.unwrap())
-    .map(|m| m.as_str())
-    .map(|s| s.parse::<usize>().map_err(|e| e.to_string()))
-    .collect::<Result<Vec<usize>, _>>()?;
+  pub fn rect(&self) -> Rect {
+    Rect { x: self.start_x, y: self.start_y, len_x: self.len_x, len_y: self.len_y }
+  }
+}
 
-  Ok(Claim { 
-    id: matches[0],
-    start_x: matches[1], 
-    start_y: matches[2], 
-    len_x: matches[3],
-    len_y: matches[4],
-  })
+fn parse(input: &String) -> std::result::Result<Claim, ParseError> {
+  let mut cursor = Cursor::new(input);
+
+  cursor.expect("#")?;
+  let id = cursor.number()?;
+  cursor.whitespace()?;
+  cursor.expect("@")?;
+  cursor.whitespace()?;
+  let start_x: usize = cursor.number()?;
+  cursor.expect(",")?;
+  let start_y: usize = cursor.number()?;
+  cursor.expect(":")?;
+  cursor.whitespace()?;
+  let len_x_column = cursor.column();
+  let len_x = cursor.number()?;
+  cursor.expect("x")?;
+  let len_y_column = cursor.column();
+  let len_y = cursor.number()?;
+  cursor.end()?;
+
+  // Every claim ends within the coordinate range, which the rest of the
+  // crate relies on when adding lengths to starting points.
+  let reaches_past = "claim reaches past the largest coordinate";
+  start_x.checked_add(len_x).ok_or_else(|| cursor.error_at(len_x_column, reaches_past))?;
+  start_y.checked_add(len_y).ok_or_else(|| cursor.error_at(len_y_column, reaches_past))?;
+
+  Ok(Claim { id, start_x, start_y, len_x, len_y })
 }
 
 fn collide(a: &Claim, b: &Claim) -> bool {
-  let a_end_x = a.start_x + a.len_x;
-  let a_end_y = a.start_y + a.len_y;
-  let b_end_x = b.start_x + b.len_x;
-  let b_end_y = b.start_y + b.len_y;
+  a.rect().intersects(&b.rect())
+}
 
-  let cond0 = a.start_x < b_end_x;
-  let cond1 = a_end_x > b.start_x;
-  let cond2 = a.start_y < b_end_y;
-  let cond3 = a_end_y > b.start_y;
+pub fn read_from<R: BufRead>(reader: R) -> Result<Vec<Claim>> {
+  parse_lines(reader, parse)
+}
 
-  cond0 && cond1 && cond2 && cond3
+pub fn parse_str(input: &str) -> Result<Vec<Claim>> {
+  read_from(input.as_bytes())
 }
 
-pub fn read_input(filepath: &str) -> Result<Vec<Claim>, String> {
-  let file = File::open(filepath).map_err(|e| e.to_string())?;
-  let claim_results = BufReader::new(file)
-    .lines()
-    .map(|line_r| parse(&line_r.map_err(|e| e.to_string())?))
-    .collect::<Result<Vec<Claim>, _>>()?;
+pub fn read_input(filepath: &str) -> Result<Vec<Claim>> {
+  read_from(BufReader::new(File::open(filepath)?))
+}
 
-  Ok(claim_results)
+pub fn calc_part1(claims: &Vec<Claim>) -> Result<usize> {
+  overlap_area(claims)
 }
 
-pub fn calc_part1(claims: &Vec<Claim>) -> usize {
-  let (size_x, size_y) = claims
-    .iter()
-    .fold((0_usize, 0_usize), |(size_x, size_y), claim| {
-      (max(size_x, claim.start_x + claim.len_x), max(size_y, claim.start_y + claim.len_y))
-    });
+/// Dense grid counting the claims covering every square inch of `bounds`,
+/// the smallest rectangle holding every claim.
+pub struct Fabric {
+  pub bounds: Rect,
+  counts: Vec<usize>,
+}
 
-  claims
-    .iter()
-    .fold(vec![0; size_x * size_y], |mut fabric, claim| {
-      for i in claim.start_x..(claim.start_x + claim.len_x) {
-        for j in claim.start_y..(claim.start_y + claim.len_y) {
-          fabric[j * size_x + i] += 1;
+impl Fabric {
+  /// Smallest rectangle holding every claim that covers any fabric.
+  pub fn bounds(claims: &Vec<Claim>) -> Rect {
+    let rects: Vec<Rect> = claims.iter().map(|c| c.rect()).filter(|r| !r.is_empty()).collect();
+
+    let (x, y) = match rects.iter().map(|r| (r.x, r.y)).reduce(|a, b| (min(a.0, b.0), min(a.1, b.1))) {
+      Some(origin) => origin,
+      None => return Rect { x: 0, y: 0, len_x: 0, len_y: 0 },
+    };
+    let (end_x, end_y) = rects
+      .iter()
+      .fold((x, y), |(end_x, end_y), r| (max(end_x, r.x + r.len_x), max(end_y, r.y + r.len_y)));
+
+    Rect { x, y, len_x: end_x - x, len_y: end_y - y }
+  }
+
+  pub fn new(claims: &Vec<Claim>) -> Fabric {
+    let bounds = Fabric::bounds(claims);
+
+    let counts = claims
+      .iter()
+      .fold(vec![0; bounds.len_x * bounds.len_y], |mut fabric, claim| {
+        for i in claim.start_x..(claim.start_x + claim.len_x) {
+          for j in claim.start_y..(claim.start_y + claim.len_y) {
+            fabric[(j - bounds.y) * bounds.len_x + (i - bounds.x)] += 1;
+          }
         }
-      }
 
-      fabric
-    })
+        fabric
+      });
+
+    Fabric { bounds, counts }
+  }
+
+  /// Claims covering the square inch at (`x`, `y`).
+  pub fn count(&self, x: usize, y: usize) -> usize {
+    match self.bounds.intersects(&Rect { x, y, len_x: 1, len_y: 1 }) {
+      true => self.counts[(y - self.bounds.y) * self.bounds.len_x + (x - self.bounds.x)],
+      false => 0,
+    }
+  }
+
+  /// Most claims covering any one square inch.
+  pub fn max_count(&self) -> usize {
+    self.counts.iter().cloned().max().unwrap_or(0)
+  }
+}
+
+/// `calc_part1` on the dense `Fabric`; simple enough to serve as the
+/// reference for `overlap_area`.
+pub fn calc_part1_dense(claims: &Vec<Claim>) -> usize {
+  Fabric::new(claims)
+    .counts
     .iter()
     .filter(|&n| *n > 1)
     .count()
 }
 
-pub fn calc_part2(claims: &Vec<Claim>) -> usize {
-  let mut memo = HashMap::new();
+pub fn calc_part2(claims: &Vec<Claim>) -> Result<usize> {
+  let index = ClaimIndex::new(claims);
 
   claims
     .iter()
-    .find(|claim_a| {
-      claims
-        .iter()
-        .filter(|claim_b| claim_a != claim_b)
-        .all(|claim_b| {
-          if memo.contains_key(&(claim_b.id, claim_a.id)) {
-            *memo.get(&(claim_b.id, claim_a.id)).unwrap()
-          } else {
-            let b = !collide(&claim_a, &claim_b);
-            memo.insert((claim_b.id, claim_a.id), b);
-            b
-          }
-        }) 
+    .find(|claim| index.overlapping(claim).is_empty())
+    .map(|claim| claim.id)
+    .ok_or_else(|| Error::NoSolution("every claim overlaps another one".to_string()))
+}
+
+/// Synthesises `size` claims of up to 30x30 inches on a 1000x1000 fabric.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+
+  (1..=size)
+    .map(|id| {
+      let len_x = rng.range(1, 30);
+      let len_y = rng.range(1, 30);
+      let start_x = rng.range(0, 1000 - len_x);
+      let start_y = rng.range(0, 1000 - len_y);
+
+      format!("#{} @ {},{}: {}x{}", id, start_x, start_y, len_x, len_y)
     })
-    .unwrap()
-    .id
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+  const DAY: u8 = 3;
+
+  type Input = Vec<Claim>;
+  type Output = usize;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    calc_part1(input)
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    calc_part2(input)
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  /// The claims of the puzzle description, shared by the tests of every
+  /// module.
+  pub(crate) fn example() -> Vec<Claim> {
+    parse_str("#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2").unwrap()
+  }
+
   #[test]
   fn parse_test() {
     assert_eq!(
@@ -154,6 +247,38 @@ mod tests {
     assert_eq!(parse(&String::from("#1 : 1,1 : 1x1")).is_ok(), false);
     assert_eq!(parse(&String::from("#1 , 1,1 , 1x1")).is_ok(), false);
     assert_eq!(parse(&String::from("#1 , 1, , x1")).is_ok(), false);
+
+    assert_eq!(
+      parse(&String::from("#1 @ 99999999999999999999999,1: 1x1")).unwrap_err().column,
+      6
+    );
+
+    let e = parse(&String::from("#1 @ 18446744073709551615,0: 5x5")).unwrap_err();
+    assert_eq!(e.column, 30);
+    assert_eq!(e.message, String::from("claim reaches past the largest coordinate"));
+    assert_eq!(parse(&String::from("#1 @ 0,18446744073709551615: 5x5")).unwrap_err().column, 32);
+    assert!(parse(&String::from("#1 @ 18446744073709551610,0: 5x5")).is_ok());
+
+    let e = parse(&String::from("#1 @ 1,1 1x1")).unwrap_err();
+    assert_eq!(e.column, 9);
+    assert_eq!(e.text, String::from("#1 @ 1,1 1x1"));
+    assert_eq!(e.message, String::from("expected \":\", found ' '"));
+  }
+
+  #[test]
+  fn parse_str_test() {
+    assert_eq!(
+      parse_str("#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n").unwrap(),
+      vec![
+        Claim { id: 1, start_x: 1, start_y: 3, len_x: 4, len_y: 4 },
+        Claim { id: 2, start_x: 3, start_y: 1, len_x: 4, len_y: 4 },
+      ]
+    );
+
+    match parse_str("#1 @ 1,3: 4x4\n#2 @ 3,1 4x4\n") {
+      Err(Error::Parse(e)) => assert_eq!((e.line, e.column), (2, 9)),
+      r => panic!("unexpected result {:?}", r),
+    }
   }
 
   #[test]
@@ -162,12 +287,18 @@ mod tests {
       Claim { id: 1, start_x: 1, start_y: 3, len_x: 4, len_y: 4 },
       Claim { id: 1, start_x: 3, start_y: 1, len_x: 4, len_y: 4 },
       Claim { id: 1, start_x: 5, start_y: 5, len_x: 2, len_y: 2 },
-    ]), 4);
+    ]).unwrap(), 4);
 
     assert_eq!(calc_part1(&vec![
       Claim { id: 1, start_x: 1, start_y: 1, len_x: 1, len_y: 2 },
       Claim { id: 1, start_x: 1, start_y: 2, len_x: 2, len_y: 1 },
-    ]), 1);
+    ]).unwrap(), 1);
+  }
+
+  #[test]
+  fn calc_part1_overflow_test() {
+    let claims = parse_str("#1 @ 0,0: 4294967296x4294967296\n#2 @ 0,0: 4294967296x4294967296").unwrap();
+    assert!(matches!(calc_part1(&claims), Err(Error::Overflow(_))));
   }
 
   #[test]
@@ -221,6 +352,92 @@ mod tests {
       Claim { id: 1, start_x: 1, start_y: 3, len_x: 4, len_y: 4 },
       Claim { id: 2, start_x: 3, start_y: 1, len_x: 4, len_y: 4 },
       Claim { id: 3, start_x: 5, start_y: 5, len_x: 2, len_y: 2 },
-    ]), 3);
+    ]).unwrap(), 3);
+
+    assert!(calc_part2(&vec![
+      Claim { id: 1, start_x: 1, start_y: 3, len_x: 4, len_y: 4 },
+      Claim { id: 2, start_x: 3, start_y: 1, len_x: 4, len_y: 4 },
+    ]).is_err());
+  }
+
+  #[test]
+  fn generate_test() {
+    assert_eq!(generate(500, 3), generate(500, 3));
+
+    let claims = parse_str(&generate(500, 3)).unwrap();
+    assert_eq!(claims.len(), 500);
+    assert!(claims.iter().all(|c| c.start_x + c.len_x <= 1000 && c.start_y + c.len_y <= 1000));
+  }
+}
+
+#[cfg(test)]
+mod proptests {
+  use super::*;
+  use proptest::prelude::*;
+  use std::collections::{HashMap, HashSet};
+
+  fn claim() -> impl Strategy<Value = Claim> {
+    (0..1000_usize, 0..20_usize, 0..20_usize, 1..10_usize, 1..10_usize)
+      .prop_map(|(id, start_x, start_y, len_x, len_y)| Claim { id, start_x, start_y, len_x, len_y })
+  }
+
+  fn cells(claim: &Claim) -> HashSet<(usize, usize)> {
+    (claim.start_x..claim.start_x + claim.len_x)
+      .flat_map(|x| (claim.start_y..claim.start_y + claim.len_y).map(move |y| (x, y)))
+      .collect()
+  }
+
+  proptest! {
+    #[test]
+    fn collide_is_symmetric(a in claim(), b in claim()) {
+      prop_assert_eq!(collide(&a, &b), collide(&b, &a));
+    }
+
+    #[test]
+    fn collide_matches_shared_cells(a in claim(), b in claim()) {
+      prop_assert_eq!(collide(&a, &b), !cells(&a).is_disjoint(&cells(&b)));
+    }
+
+    #[test]
+    fn claim_collides_with_itself(a in claim()) {
+      prop_assert!(collide(&a, &a));
+    }
+
+    #[test]
+    fn index_matches_linear_scan(
+      claims in prop::collection::vec(claim(), 0..40),
+      region in claim(),
+      (x, y) in (0..30_usize, 0..30_usize),
+    ) {
+      let index = ClaimIndex::new(&claims);
+      let scan = |rect: &Rect| claims
+        .iter()
+        .filter(|c| c.rect().intersects(rect))
+        .collect::<Vec<&Claim>>();
+
+      prop_assert_eq!(index.in_region(&region.rect()), scan(&region.rect()));
+      prop_assert_eq!(index.at_point(x, y), scan(&Rect { x, y, len_x: 1, len_y: 1 }));
+
+      for claim in claims.iter() {
+        let others: Vec<&Claim> = claims.iter().filter(|c| *c != claim && collide(c, claim)).collect();
+        prop_assert_eq!(index.overlapping(claim), others);
+      }
+    }
+
+    #[test]
+    fn overlap_area_matches_dense_grid(claims in prop::collection::vec(claim(), 0..40)) {
+      prop_assert_eq!(overlap_area(&claims).unwrap(), calc_part1_dense(&claims));
+    }
+
+    #[test]
+    fn calc_part1_matches_oracle(claims in prop::collection::vec(claim(), 0..20)) {
+      let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+
+      for cell in claims.iter().flat_map(cells) {
+        *counts.entry(cell).or_insert(0) += 1;
+      }
+
+      prop_assert_eq!(calc_part1(&claims).unwrap(), counts.values().filter(|&&n| n > 1).count());
+    }
   }
 }
@@ -1,11 +1,17 @@
-use day3::read_input;
+use std::env;
+
+use common::{Result, Source};
+
+use day3::read_from;
 use day3::calc_part1;
 use day3::calc_part2;
 
-fn main() {
-  let filepath = "./input.txt";
-  let claims = read_input(&filepath).unwrap();
+fn main() -> Result<()> {
+  let source = Source::from_arg(&env::args().nth(1).unwrap_or_else(|| "./input.txt".to_string()));
+  let claims = read_from(source.reader()?)?;
+
+  println!("Day #3, part #1 {}", calc_part1(&claims)?);
+  println!("Day #3, part #2 {}", calc_part2(&claims)?);
 
-  println!("Day #3, part #1 {}", calc_part1(&claims));
-  println!("Day #3, part #2 {}", calc_part2(&claims));
+  Ok(())
 }
\ No newline at end of file
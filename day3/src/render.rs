@@ -0,0 +1,202 @@
+use common::{Error, Result};
+
+use crate::{calc_part2, Claim, Fabric, Rect};
+
+type Rgb = [u8; 3];
+
+const BACKGROUND: Rgb = [24, 24, 32];
+const CLAIMED: Rgb = [48, 64, 112];
+const OUTLINE: Rgb = [160, 160, 176];
+const INTACT: Rgb = [64, 200, 96];
+const COOL: Rgb = [255, 220, 64];
+const HOT: Rgb = [224, 32, 32];
+
+fn intact(claims: &Vec<Claim>) -> Option<&Claim> {
+  let id = calc_part2(claims).ok()?;
+  claims.iter().find(|claim| claim.id == id)
+}
+
+fn covers(claim: &Claim, x: usize, y: usize) -> bool {
+  claim.rect().intersects(&Rect { x, y, len_x: 1, len_y: 1 })
+}
+
+/// Overlaps shade from yellow to red as they approach the densest spot.
+fn heat(count: usize, max_count: usize) -> Rgb {
+  let t = (count - 2) as f64 / max_count.saturating_sub(2).max(1) as f64;
+  let mix = |cool: u8, hot: u8| (cool as f64 + (hot as f64 - cool as f64) * t).round() as u8;
+
+  [mix(COOL[0], HOT[0]), mix(COOL[1], HOT[1]), mix(COOL[2], HOT[2])]
+}
+
+/// Widest and tallest fabric `render_ascii` draws, in square inches.
+pub const MAX_ASCII_SIDE: usize = 1000;
+
+/// Most pixels `render_ppm` puts in one image.
+pub const MAX_PIXELS: usize = 1 << 24;
+
+/// One character per square inch: `.` unclaimed, `#` claimed once, `2` to
+/// `9` for overlaps, `+` for ten or more and `O` for the intact claim.
+/// Claims are not outlined, as a character has no room to spare for that;
+/// meant for fabrics small enough to read in a terminal, so only the
+/// rectangle holding the claims is drawn and at most `MAX_ASCII_SIDE` wide
+/// and tall.
+pub fn render_ascii(claims: &Vec<Claim>) -> Result<String> {
+  let bounds = Fabric::bounds(claims);
+
+  if bounds.len_x > MAX_ASCII_SIDE || bounds.len_y > MAX_ASCII_SIDE {
+    return Err(Error::TooLarge(format!(
+      "{}x{} fabric does not fit in {} characters a side",
+      bounds.len_x, bounds.len_y, MAX_ASCII_SIDE
+    )));
+  }
+
+  let fabric = Fabric::new(claims);
+  let intact = intact(claims);
+
+  Ok((bounds.y..bounds.y + bounds.len_y)
+    .map(|y| {
+      let mut row: String = (bounds.x..bounds.x + bounds.len_x)
+        .map(|x| match fabric.count(x, y) {
+          _ if intact.is_some_and(|claim| covers(claim, x, y)) => 'O',
+          0 => '.',
+          1 => '#',
+          n if n < 10 => (b'0' + n as u8) as char,
+          _ => '+',
+        })
+        .collect();
+
+      row.push('\n');
+      row
+    })
+    .collect())
+}
+
+/// Binary PPM image of the rectangle holding the claims, `scale` pixels to
+/// the inch: claimed fabric in blue, overlaps as a heatmap, every claim
+/// outlined in grey and the intact claim filled in green. Images of more
+/// than `MAX_PIXELS` pixels are refused.
+pub fn render_ppm(claims: &Vec<Claim>, scale: usize) -> Result<Vec<u8>> {
+  let scale = scale.max(1);
+  let bounds = Fabric::bounds(claims);
+  let too_large = || Error::TooLarge(format!(
+    "{}x{} fabric at {} pixels to the inch takes more than {} pixels",
+    bounds.len_x, bounds.len_y, scale, MAX_PIXELS
+  ));
+
+  let width = bounds.len_x.checked_mul(scale).ok_or_else(too_large)?;
+  let height = bounds.len_y.checked_mul(scale).ok_or_else(too_large)?;
+
+  if width.checked_mul(height).is_none_or(|pixels| pixels > MAX_PIXELS) {
+    return Err(too_large());
+  }
+
+  let fabric = Fabric::new(claims);
+  let max_count = fabric.max_count();
+
+  let mut pixels: Vec<Rgb> = (0..width * height)
+    .map(|i| match fabric.count(bounds.x + i % width / scale, bounds.y + i / width / scale) {
+      0 => BACKGROUND,
+      1 => CLAIMED,
+      n => heat(n, max_count),
+    })
+    .collect();
+
+  let scaled = |claim: &Claim| {
+    let (x, y) = (claim.start_x - bounds.x, claim.start_y - bounds.y);
+    (x * scale, y * scale, (x + claim.len_x) * scale, (y + claim.len_y) * scale)
+  };
+
+  for claim in claims.iter().filter(|claim| !claim.rect().is_empty()) {
+    let (from_x, from_y, to_x, to_y) = scaled(claim);
+
+    for x in from_x..to_x {
+      pixels[from_y * width + x] = OUTLINE;
+      pixels[(to_y - 1) * width + x] = OUTLINE;
+    }
+    for y in from_y..to_y {
+      pixels[y * width + from_x] = OUTLINE;
+      pixels[y * width + to_x - 1] = OUTLINE;
+    }
+  }
+
+  if let Some(claim) = intact(claims) {
+    let (from_x, from_y, to_x, to_y) = scaled(claim);
+
+    for y in from_y..to_y {
+      for pixel in pixels[y * width + from_x..y * width + to_x].iter_mut() {
+        *pixel = INTACT;
+      }
+    }
+  }
+
+  let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+  image.extend(pixels.iter().flatten());
+  Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tests::example;
+
+  #[test]
+  fn render_ascii_test() {
+    assert_eq!(
+      render_ascii(&example()).unwrap(),
+      [
+        "..####",
+        "..####",
+        "##22##",
+        "##22##",
+        "####OO",
+        "####OO",
+      ].iter().map(|row| format!("{}\n", row)).collect::<String>()
+    );
+
+    assert_eq!(render_ascii(&vec![]).unwrap(), String::new());
+    assert_eq!(render_ascii(&crate::parse_str("#1 @ 1000000,1000000: 2x1").unwrap()).unwrap(), "OO\n");
+  }
+
+  #[test]
+  fn render_ppm_test() {
+    let header = b"P6\n18 18\n255\n";
+    let image = render_ppm(&example(), 3).unwrap();
+    let pixel = |x: usize, y: usize| {
+      let i = header.len() + (y * 18 + x) * 3;
+      [image[i], image[i + 1], image[i + 2]]
+    };
+
+    assert!(image.starts_with(header));
+    assert_eq!(image.len(), header.len() + 18 * 18 * 3);
+
+    assert_eq!(pixel(0, 0), BACKGROUND);
+    assert_eq!(pixel(0, 6), OUTLINE);
+    assert_eq!(pixel(1, 7), CLAIMED);
+    assert_eq!(pixel(7, 7), heat(2, 2));
+    assert_eq!(pixel(12, 12), INTACT);
+    assert_eq!(pixel(17, 17), INTACT);
+  }
+
+  #[test]
+  fn size_limits_test() {
+    let wide = crate::parse_str("#1 @ 0,0: 1001x1").unwrap();
+    let far_apart = crate::parse_str("#1 @ 0,0: 1x1\n#2 @ 5000,0: 1x1").unwrap();
+    let huge = crate::parse_str("#1 @ 0,0: 1000000000x1000000000").unwrap();
+
+    assert!(matches!(render_ascii(&wide), Err(Error::TooLarge(_))));
+    assert!(matches!(render_ascii(&far_apart), Err(Error::TooLarge(_))));
+    assert!(matches!(render_ascii(&huge), Err(Error::TooLarge(_))));
+
+    assert!(render_ppm(&wide, 4).is_ok());
+    assert!(matches!(render_ppm(&wide, 5000), Err(Error::TooLarge(_))));
+    assert!(matches!(render_ppm(&example(), usize::MAX), Err(Error::TooLarge(_))));
+    assert!(matches!(render_ppm(&huge, 1), Err(Error::TooLarge(_))));
+  }
+
+  #[test]
+  fn heat_test() {
+    assert_eq!(heat(2, 2), COOL);
+    assert_eq!(heat(2, 6), COOL);
+    assert_eq!(heat(6, 6), HOT);
+  }
+}
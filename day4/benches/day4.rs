@@ -0,0 +1,41 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::Solution;
+use day4::Day4;
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// Replays the guard log over `factor` consecutive years.
+fn scale(factor: usize) -> String {
+  (0..factor)
+    .map(|k| INPUT.trim_end().replace("[1518-", &format!("[{}-", 1518 + k)))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day4");
+  group.sample_size(10);
+
+  for factor in [1, 4, 16].iter() {
+    let text = scale(*factor);
+    let input = Day4::parse_str(&text).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", factor), &text, |b, text| {
+      b.iter(|| Day4::parse_str(black_box(text)))
+    });
+    group.bench_with_input(BenchmarkId::new("part1", factor), &input, |b, input| {
+      b.iter(|| Day4::part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", factor), &input, |b, input| {
+      b.iter(|| Day4::part2(black_box(input)))
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
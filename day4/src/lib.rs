@@ -3,11 +3,11 @@ use std::fs::File;
 use std::io::{BufReader, BufRead};
 use std::collections::HashMap;
 
-use regex::Regex;
-
 use chrono::prelude::*;
 use chrono::Duration;
 
+use common::{parse_lines, Cursor, Error, ParseError, Result, Rng, Solution};
+
 #[derive(Debug, PartialEq)]
 enum Action {
   Shift(usize),
@@ -28,58 +28,61 @@ struct AsleepRecord {
   duration: Duration
 }
 
-fn parse(input: &String) -> Result<Record, String> {
-  let datetime_re = Regex::new(r"\[(\d+)-(\d+)-(\d+)\s(\d+):(\d+)\]")
-    .map_err(|e| e.to_string())?;
-  let shift_re = Regex::new(r".+#(\d+).+")
-    .map_err(|e| e.to_string())?;
+fn parse_datetime(cursor: &mut Cursor) -> std::result::Result<DateTime<Utc>, ParseError> {
+  let column = cursor.column();
+
+  cursor.expect("[")?;
+  let year = cursor.number()?;
+  cursor.expect("-")?;
+  let month = cursor.number()?;
+  cursor.expect("-")?;
+  let day = cursor.number()?;
+  cursor.whitespace()?;
+  let hour = cursor.number()?;
+  cursor.expect(":")?;
+  let minute = cursor.number()?;
+  cursor.expect("]")?;
+
+  Utc
+    .with_ymd_and_hms(year, month, day, hour, minute, 0)
+    .single()
+    .ok_or_else(|| cursor.error_at(column, "invalid datetime"))
+}
 
-  if input.len() < 18 {
-    return Err("length of input does not meet the criteria".to_string());
+fn parse_action(cursor: &mut Cursor) -> std::result::Result<Action, ParseError> {
+  if cursor.is_next("Guard") {
+    cursor.expect("Guard")?;
+    cursor.whitespace()?;
+    cursor.expect("#")?;
+    let id = cursor.number()?;
+    cursor.whitespace()?;
+    cursor.expect("begins shift")?;
+
+    Ok(Action::Shift(id))
+  } else if cursor.is_next("wakes up") {
+    cursor.expect("wakes up")?;
+    Ok(Action::WakeUp)
+  } else if cursor.is_next("falls asleep") {
+    cursor.expect("falls asleep")?;
+    Ok(Action::FallAsleep)
+  } else {
+    Err(cursor.error("expected \"Guard #<id> begins shift\", \"falls asleep\" or \"wakes up\""))
   }
+}
 
-  let (datetime_input, action_input) = input.split_at(18);
-  let datetime = datetime_re
-    .captures(datetime_input)
-    .ok_or("could not parse datetime")?
-    .iter()
-    .skip(1)
-    .map(|o| o.unwrap())
-    .map(|m| m.as_str())
-    .map(|s| s.parse::<usize>().map_err(|e| e.to_string()))
-    .collect::<Result<Vec<usize>, _>>()?;
-
-  let action = if shift_re.is_match(action_input) {
-    let shift_cap = shift_re
-      .captures(action_input)
-      .ok_or("could not parse shift action")?;
-
-    let id = shift_cap[1]
-      .parse::<usize>()
-      .map_err(|e| e.to_string())?;
-
-    Action::Shift(id)
-  } else {
-    match action_input.trim() {
-      "wakes up" => Action::WakeUp,
-      "falls asleep" => Action::FallAsleep,
-      _ => return Err("could not parse action".to_string()),
-    }
-  };
+fn parse(input: &String) -> std::result::Result<Record, ParseError> {
+  let mut cursor = Cursor::new(input);
 
-  let datetime = Utc
-    .ymd(datetime[0] as i32, datetime[1] as u32, datetime[2] as u32)
-    .and_hms(datetime[3] as u32, datetime[4] as u32, 0);
+  let datetime = parse_datetime(&mut cursor)?;
+  cursor.whitespace()?;
+  let action = parse_action(&mut cursor)?;
+  cursor.end()?;
 
   Ok(Record { datetime, action })
 }
 
-pub fn read_input(filepath: &str) -> Result<Vec<Record>, String> {
-  let file = File::open(filepath).map_err(|e| e.to_string())?;
-  let mut claim_results = BufReader::new(file)
-    .lines()
-    .map(|line_r| parse(&line_r.map_err(|e| e.to_string())?))
-    .collect::<Result<Vec<Record>, _>>()?;
+pub fn read_from<R: BufRead>(reader: R) -> Result<Vec<Record>> {
+  let mut claim_results = parse_lines(reader, parse)?;
     
   claim_results.sort_by(|a, b| if a.datetime.lt(&b.datetime) { 
       Ordering::Less 
@@ -90,35 +93,58 @@ pub fn read_input(filepath: &str) -> Result<Vec<Record>, String> {
   Ok(claim_results)
 }
 
-fn filter_map_asleep(records: &Vec<Record>) -> Vec<AsleepRecord> {
+pub fn parse_str(input: &str) -> Result<Vec<Record>> {
+  read_from(input.as_bytes())
+}
+
+pub fn read_input(filepath: &str) -> Result<Vec<Record>> {
+  read_from(BufReader::new(File::open(filepath)?))
+}
+
+/// Naps taken during the midnight hour. A guard waking up without having
+/// fallen asleep earlier that same hour leaves the log without a solution.
+fn filter_map_asleep(records: &Vec<Record>) -> Result<Vec<AsleepRecord>> {
   records
     .iter()
-    .scan((0_usize, Utc::now()), |(id, start), r| {
+    .scan((0_usize, None as Option<DateTime<Utc>>), |(id, start), r| {
       match r.action {
         Action::Shift(new_id) => {
           *id = new_id;
-          Some(None)
+          *start = None;
+          Some(Ok(None))
         },
         Action::FallAsleep => {
-          *start = r.datetime;
-          Some(None)
+          *start = Some(r.datetime);
+          Some(Ok(None))
         },
         Action::WakeUp => {
-          // ToDo: is this enough to fully describe the problem?
-          let duration = Duration::minutes(
-            (r.datetime.time().minute() - start.time().minute()) as i64
-          );
-          
-          Some(Some(AsleepRecord {
-            id: *id, 
-            datetime: *start, 
-            duration 
-          }))
+          let asleep = start.take().and_then(|start| {
+            let minutes = (r.datetime - start).num_minutes();
+
+            if minutes < 0 || start.time().minute() as i64 + minutes > 60 {
+              return None;
+            }
+
+            Some(AsleepRecord {
+              id: *id,
+              datetime: start,
+              duration: Duration::minutes(minutes)
+            })
+          });
+
+          Some(match asleep {
+            Some(asleep) if asleep.datetime.time().hour() == 0 => Ok(Some(asleep)),
+            Some(_) => Ok(None),
+            None => Err(Error::NoSolution(format!(
+              "guard #{} wakes up at {} without having fallen asleep earlier that hour",
+              id,
+              r.datetime.format("%Y-%m-%d %H:%M")
+            ))),
+          })
         }
       }
     })
-    .filter_map(|o| o)
-    .filter(|r| r.datetime.time().hour() == 0)
+    .filter_map(|r| r.transpose())
     .collect()
 }
 
@@ -161,7 +187,7 @@ fn find_intersection_beginning(a: &AsleepRecord, b: &AsleepRecord) -> Option<Dat
 fn find_sleepiest_minute(records: &Vec<AsleepRecord>) -> Option<u8> {
   records
     .iter()
-    .take(records.len() - 1)
+    .take(records.len().saturating_sub(1))
     .enumerate()
     .flat_map(|(i, a)| {
       records
@@ -187,10 +213,7 @@ fn find_sleepiest_minute(records: &Vec<AsleepRecord>) -> Option<u8> {
 fn find_sleepiest_id(records: &Vec<AsleepRecord>, t: &NaiveTime) -> Option<usize> {
   records
     .iter()
-    .filter(|r| match get_is_intersecting(&r, &t) {
-      Some(b) => b,
-      _ => false,
-    })
+    .filter(|r| get_is_intersecting(&r, &t).unwrap_or_default())
     .map(|r| r.id)
     .fold(HashMap::new() as HashMap<usize, usize>, |mut map, id| {
       let entry = map.entry(id).or_insert(0);
@@ -207,10 +230,10 @@ fn find_sleepiest_id(records: &Vec<AsleepRecord>, t: &NaiveTime) -> Option<usize
     .map(|(id, _)| *id)
 }
 
-pub fn calc_part1(records: &Vec<Record>) -> usize {
-  let sleep_records = filter_map_asleep(&records);
+pub fn calc_part1(records: &Vec<Record>) -> Result<usize> {
+  let sleep_records = filter_map_asleep(&records)?;
   let sleepiest_id = find_sleepiest(&sleep_records)
-    .unwrap();
+    .ok_or_else(|| Error::NoSolution("no guard has fallen asleep".to_string()))?;
 
   let sleepiest_records: Vec<AsleepRecord> = sleep_records
     .into_iter()
@@ -218,20 +241,82 @@ pub fn calc_part1(records: &Vec<Record>) -> usize {
     .collect();
 
   let sleepiest_minute: usize = find_sleepiest_minute(&sleepiest_records)
-    .expect("cound not calculate the sleepies minute") as usize;
+    .ok_or_else(|| Error::NoSolution("could not calculate the sleepiest minute".to_string()))?
+    as usize;
 
-  sleepiest_id * sleepiest_minute
+  Ok(sleepiest_id * sleepiest_minute)
 }
 
-pub fn calc_part2(records: &Vec<Record>) -> usize {
-  let sleep_records = filter_map_asleep(&records);
+pub fn calc_part2(records: &Vec<Record>) -> Result<usize> {
+  let sleep_records = filter_map_asleep(&records)?;
   let sleepiest_minute: usize = find_sleepiest_minute(&sleep_records)
-    .expect("cound not calculate the sleepies minute") as usize;
-  let t = NaiveTime::from_hms(0, sleepiest_minute as u32, 0);
+    .ok_or_else(|| Error::NoSolution("could not calculate the sleepiest minute".to_string()))?
+    as usize;
+  let t = NaiveTime::from_hms_opt(0, sleepiest_minute as u32, 0)
+    .ok_or_else(|| Error::NoSolution("sleepiest minute is out of range".to_string()))?;
   let sleepiest_id = find_sleepiest_id(&sleep_records, &t)
-    .expect("count not calculate the sleepiest id");
+    .ok_or_else(|| Error::NoSolution("could not calculate the sleepiest id".to_string()))?;
+
+  Ok(sleepiest_id * sleepiest_minute)
+}
+
+/// Synthesises the unsorted log of `size` consecutive guard shifts, each
+/// with up to three naps during the midnight hour.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+  let guards: Vec<i64> = (0..size / 10 + 1).map(|_| rng.range(1, 4000)).collect();
+  let first_day = NaiveDate::from_ymd_opt(1518, 1, 1).unwrap();
+  let mut lines = vec![];
+
+  for k in 0..size {
+    let day = first_day + Duration::days(k as i64);
+    let id = guards[rng.below(guards.len() as u64) as usize];
+
+    let first_minute = if rng.chance(1, 2) {
+      let eve = day - Duration::days(1);
+      lines.push(format!("[{} 23:{:02}] Guard #{} begins shift", eve.format("%Y-%m-%d"), rng.range(45, 59), id));
+      0
+    } else {
+      let minute = rng.range(0, 5);
+      lines.push(format!("[{} 00:{:02}] Guard #{} begins shift", day.format("%Y-%m-%d"), minute, id));
+      minute + 1
+    };
+
+    let mut minutes: Vec<i64> = (first_minute..60).collect();
+    rng.shuffle(&mut minutes);
+    let naps = rng.below(4) as usize;
+    let mut minutes: Vec<i64> = minutes.into_iter().take(naps * 2).collect();
+    minutes.sort();
+
+    for (i, minute) in minutes.iter().enumerate() {
+      let action = if i % 2 == 0 { "falls asleep" } else { "wakes up" };
+      lines.push(format!("[{} 00:{:02}] {}", day.format("%Y-%m-%d"), minute, action));
+    }
+  }
+
+  rng.shuffle(&mut lines);
+  lines.join("\n")
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+  const DAY: u8 = 4;
+
+  type Input = Vec<Record>;
+  type Output = usize;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    calc_part1(input)
+  }
 
-  sleepiest_id * sleepiest_minute
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    calc_part2(input)
+  }
 }
 
 #[cfg(test)]
@@ -246,7 +331,7 @@ mod tests {
       .unwrap();
 
     let expected = Record {
-      datetime: Utc.ymd(1970, 1, 1).and_hms(23, 59, 0),
+      datetime: Utc.with_ymd_and_hms(1970, 1, 1, 23, 59, 0).unwrap(),
       action: Action::Shift(1)
     };
 
@@ -264,150 +349,197 @@ mod tests {
     assert!(parse(&String::from("[1970-01-01 23:59]")).is_err());
     assert!(parse(&String::from("Guard #1 begins shift")).is_err());
     assert!(parse(&String::from("")).is_err());
+    assert!(parse(&String::from("[1970-01-01 23:59] wakes up!")).is_err());
 
     assert!(
       parse(&String::from("[1970-01-01 23:59] Guard #1 begins shift")).is_ok()
     );
   }
 
+  #[test]
+  fn parse_error_test() {
+    let e = parse(&String::from("[1518-11-01 00:05] fall asleep")).unwrap_err();
+    assert_eq!(e.column, 20);
+
+    let e = parse(&String::from("[1518-13-01 00:05] wakes up")).unwrap_err();
+    assert_eq!(e.column, 1);
+    assert_eq!(e.message, String::from("invalid datetime"));
+
+    let e = parse(&String::from("[1518-11-01 00:05]")).unwrap_err();
+    assert_eq!(e.column, 19);
+
+    let e = parse(&String::from("[1518-11-01 00:05] Guard #x begins shift")).unwrap_err();
+    assert_eq!(e.column, 27);
+
+    assert!(parse(&String::from("[1518-11-01 00:05]\u{e9}\u{e9}wakes up")).is_err());
+  }
+
   #[test]
   fn filter_map_asleep_test() {
     let v = vec![
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 0, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 0, 0).unwrap(),
         action: Action::Shift(10)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 5, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 5, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 25, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 25, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 30, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 30, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 55, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 55, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(23, 58, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 23, 58, 0).unwrap(),
         action: Action::Shift(99)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 2).and_hms(0, 40, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 2, 0, 40, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 2).and_hms(0, 50, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 2, 0, 50, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 5, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 5, 0).unwrap(),
         action: Action::Shift(10)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 24, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 24, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 29, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 29, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 2, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 2, 0).unwrap(),
         action: Action::Shift(99)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 36, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 36, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 46, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 46, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 3, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 3, 0).unwrap(),
         action: Action::Shift(99)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 45, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 45, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 55, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 55, 0).unwrap(),
         action: Action::WakeUp
       },
     ];
 
-    assert_eq!(filter_map_asleep(&v), vec![
+    assert_eq!(filter_map_asleep(&v).unwrap(), vec![
       AsleepRecord { 
         id: 10, 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 5, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 5, 0).unwrap(),
         duration: Duration::minutes(20)
       },
       AsleepRecord { 
         id: 10, 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 30, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 30, 0).unwrap(),
         duration: Duration::minutes(25)
       },
       AsleepRecord { 
         id: 99, 
-        datetime: Utc.ymd(1518, 11, 2).and_hms(0, 40, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 2, 0, 40, 0).unwrap(),
         duration: Duration::minutes(10)
       },
       AsleepRecord { 
         id: 10, 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 24, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 24, 0).unwrap(),
         duration: Duration::minutes(5)
       },
       AsleepRecord { 
         id: 99, 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 36, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 36, 0).unwrap(),
         duration: Duration::minutes(10)
       },
       AsleepRecord {
         id: 99,
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 45, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 45, 0).unwrap(),
         duration: Duration::minutes(10)
       },
     ]);
   }
 
+  #[test]
+  fn filter_map_asleep_errors_test() {
+    let record = |hour: u32, minute: u32, action: Action| Record {
+      datetime: Utc.with_ymd_and_hms(1518, 11, 1, hour, minute, 0).unwrap(),
+      action
+    };
+
+    let unmatched = vec![record(0, 0, Action::Shift(10)), record(0, 5, Action::WakeUp)];
+    assert!(matches!(filter_map_asleep(&unmatched), Err(Error::NoSolution(_))));
+    assert!(matches!(calc_part1(&unmatched), Err(Error::NoSolution(_))));
+
+    let past_the_hour = vec![
+      record(0, 0, Action::Shift(10)),
+      record(0, 50, Action::FallAsleep),
+      record(1, 5, Action::WakeUp),
+    ];
+    assert!(matches!(calc_part1(&past_the_hour), Err(Error::NoSolution(_))));
+    assert!(matches!(calc_part2(&past_the_hour), Err(Error::NoSolution(_))));
+
+    let twice = vec![
+      record(0, 0, Action::Shift(10)),
+      record(0, 10, Action::FallAsleep),
+      record(0, 20, Action::WakeUp),
+      record(0, 30, Action::WakeUp),
+    ];
+    assert!(matches!(filter_map_asleep(&twice), Err(Error::NoSolution(_))));
+  }
+
   #[test]
   fn find_sleepiest_test() {
     assert_eq!(Some(10), find_sleepiest(&vec![
       AsleepRecord { 
         id: 10, 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 5, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 5, 0).unwrap(),
         duration: Duration::minutes(20)
       },
       AsleepRecord { 
         id: 10, 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 30, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 30, 0).unwrap(),
         duration: Duration::minutes(25)
       },
       AsleepRecord { 
         id: 99, 
-        datetime: Utc.ymd(1518, 11, 2).and_hms(0, 40, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 2, 0, 40, 0).unwrap(),
         duration: Duration::minutes(10)
       },
       AsleepRecord { 
         id: 10, 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 24, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 24, 0).unwrap(),
         duration: Duration::minutes(5)
       },
       AsleepRecord { 
         id: 99, 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 36, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 36, 0).unwrap(),
         duration: Duration::minutes(10)
       },
       AsleepRecord {
         id: 99,
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 45, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 45, 0).unwrap(),
         duration: Duration::minutes(10)
       },
     ]));
@@ -417,93 +549,116 @@ mod tests {
   fn find_intersection_beginning_test() {
     let a = AsleepRecord { 
       id: 10, 
-      datetime: Utc.ymd(1518, 11, 1).and_hms(0, 5, 0),
+      datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 5, 0).unwrap(),
       duration: Duration::minutes(20)
     };
 
     let b = AsleepRecord { 
       id: 10, 
-      datetime: Utc.ymd(1518, 11, 3).and_hms(0, 24, 0),
+      datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 24, 0).unwrap(),
       duration: Duration::minutes(5)
     };
 
     let result = find_intersection_beginning(&a, &b);
 
     assert!(result.is_some());
-    assert_eq!(result.unwrap().time(), NaiveTime::from_hms(0, 24, 0));
+    assert_eq!(result.unwrap().time(), NaiveTime::from_hms_opt(0, 24, 0).unwrap());
   }
 
   #[test]
   fn calc_part1_test() {
     assert_eq!(240, calc_part1(&vec![
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 0, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 0, 0).unwrap(),
         action: Action::Shift(10)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 5, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 5, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 25, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 25, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 30, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 30, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(0, 55, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 0, 55, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 1).and_hms(23, 58, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 1, 23, 58, 0).unwrap(),
         action: Action::Shift(99)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 2).and_hms(0, 40, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 2, 0, 40, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 2).and_hms(0, 50, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 2, 0, 50, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 5, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 5, 0).unwrap(),
         action: Action::Shift(10)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 24, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 24, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 3).and_hms(0, 29, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 3, 0, 29, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 2, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 2, 0).unwrap(),
         action: Action::Shift(99)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 36, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 36, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 4).and_hms(0, 46, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 4, 0, 46, 0).unwrap(),
         action: Action::WakeUp
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 3, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 3, 0).unwrap(),
         action: Action::Shift(99)
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 45, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 45, 0).unwrap(),
         action: Action::FallAsleep
       },
       Record { 
-        datetime: Utc.ymd(1518, 11, 5).and_hms(0, 55, 0),
+        datetime: Utc.with_ymd_and_hms(1518, 11, 5, 0, 55, 0).unwrap(),
         action: Action::WakeUp
       },
-    ]));
+    ]).unwrap());
+
+    assert!(calc_part1(&vec![]).is_err());
+    assert!(calc_part2(&vec![]).is_err());
+  }
+
+  #[test]
+  fn checked_in_input_test() {
+    let records = read_input("input.txt").unwrap();
+
+    assert_eq!(calc_part1(&records).unwrap(), 38813);
+    assert_eq!(calc_part2(&records).unwrap(), 141071);
+  }
+
+  #[test]
+  fn generate_test() {
+    assert_eq!(generate(200, 3), generate(200, 3));
+
+    let records = parse_str(&generate(200, 3)).unwrap();
+    let shifts = records.iter().filter(|r| matches!(r.action, Action::Shift(_))).count();
+    assert_eq!(shifts, 200);
+    assert!(records.windows(2).all(|w| w[0].datetime <= w[1].datetime));
+    assert!(calc_part1(&records).is_ok());
+    assert!(calc_part2(&records).is_ok());
   }
 }
@@ -1,11 +1,17 @@
-use day4::read_input;
+use std::env;
+
+use common::{Result, Source};
+
+use day4::read_from;
 use day4::calc_part1;
 use day4::calc_part2;
 
-fn main() {
-  let filepath = "./input.txt";
-  let records = read_input(&filepath).unwrap();
+fn main() -> Result<()> {
+  let source = Source::from_arg(&env::args().nth(1).unwrap_or_else(|| "./input.txt".to_string()));
+  let records = read_from(source.reader()?)?;
+
+  println!("Day #4, part #1 {:?}", calc_part1(&records)?);
+  println!("Day #4, part #2 {:?}", calc_part2(&records)?);
 
-  println!("Day #4, part #1 {:?}", calc_part1(&records));
-  println!("Day #4, part #2 {:?}", calc_part2(&records));
+  Ok(())
 }
\ No newline at end of file
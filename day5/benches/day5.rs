@@ -0,0 +1,38 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::Solution;
+use day5::Day5;
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// Concatenates `factor` copies of the polymer.
+fn scale(factor: usize) -> String {
+  INPUT.trim_end().repeat(factor)
+}
+
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day5");
+  group.sample_size(10);
+
+  for factor in [1, 4, 16].iter() {
+    let text = scale(*factor);
+    let input = Day5::parse_str(&text).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", factor), &text, |b, text| {
+      b.iter(|| Day5::parse_str(black_box(text)))
+    });
+    group.bench_with_input(BenchmarkId::new("part1", factor), &input, |b, input| {
+      b.iter(|| Day5::part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", factor), &input, |b, input| {
+      b.iter(|| Day5::part2(black_box(input)))
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
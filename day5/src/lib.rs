@@ -1,21 +1,40 @@
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{BufReader, BufRead};
 use std::collections::HashSet;
 use std::collections::BinaryHeap;
 
-pub fn read_input(filepath: &str) -> Result<(HashSet<char>, Vec<char>), String> {
-  let mut file = File::open(filepath).map_err(|e| e.to_string())?;
-  let mut contents = String::new();
-  file.read_to_string(&mut contents).map_err(|e| e.to_string())?;
+use common::{ParseError, Result, Rng, Solution};
 
-  Ok(contents
+fn parse(input: &str) -> std::result::Result<(HashSet<char>, Vec<char>), ParseError> {
+  input
+    .trim_end()
     .chars()
-    .fold((HashSet::new(), Vec::new()), |(mut s, mut v), c| {
+    .enumerate()
+    .try_fold((HashSet::new(), Vec::new()), |(mut s, mut v), (i, c)| {
+      if !c.is_ascii_alphabetic() {
+        return Err(ParseError::new(input, i + 1, &format!("unexpected unit {:?}", c)));
+      }
+
       s.insert(c.to_ascii_lowercase());
       v.push(c);
 
-      (s, v)
-    }))
+      Ok((s, v))
+    })
+}
+
+pub fn read_from<R: BufRead>(mut reader: R) -> Result<(HashSet<char>, Vec<char>)> {
+  let mut contents = String::new();
+  reader.read_to_string(&mut contents)?;
+
+  Ok(parse(&contents)?)
+}
+
+pub fn parse_str(input: &str) -> Result<(HashSet<char>, Vec<char>)> {
+  read_from(input.as_bytes())
+}
+
+pub fn read_input(filepath: &str) -> Result<(HashSet<char>, Vec<char>)> {
+  read_from(BufReader::new(File::open(filepath)?))
 }
 
 fn get_is_matching(a: &char, b: &char) -> bool {
@@ -23,13 +42,11 @@ fn get_is_matching(a: &char, b: &char) -> bool {
     || (a.is_lowercase() && a.to_ascii_uppercase() == *b)
 }
 
-pub fn calc_part1(input: &Vec<char>) -> usize {
-  let mut heap: BinaryHeap<usize> = [0]
-    .into_iter()
-    .cloned()
-    .collect();
+/// Indices of the units that survive the full reaction.
+fn reduce(input: &Vec<char>) -> BinaryHeap<usize> {
+  let mut heap: BinaryHeap<usize> = BinaryHeap::new();
 
-  for (i, c) in input.iter().enumerate().skip(1) {
+  for (i, c) in input.iter().enumerate() {
     match heap.peek() {
       None => heap.push(i),
       Some(j) => match get_is_matching(c, &input[*j]) {
@@ -39,7 +56,20 @@ pub fn calc_part1(input: &Vec<char>) -> usize {
     };
   }
 
-  heap.len()
+  heap
+}
+
+/// Polymer left over once every reacting pair has been removed.
+pub fn react(input: &Vec<char>) -> Vec<char> {
+  reduce(input)
+    .into_sorted_vec()
+    .into_iter()
+    .map(|i| input[i])
+    .collect()
+}
+
+pub fn calc_part1(input: &Vec<char>) -> usize {
+  reduce(input).len()
 }
 
 pub fn calc_part2((s, v): &(HashSet<char>, Vec<char>)) -> usize {
@@ -47,7 +77,7 @@ pub fn calc_part2((s, v): &(HashSet<char>, Vec<char>)) -> usize {
     .iter()
     .map(|c| calc_part1(
       &v
-        .into_iter()
+        .iter()
         .cloned()
         .filter(|v_c| *c != v_c.to_ascii_lowercase() )
         .collect::<Vec<char>>()
@@ -56,10 +86,67 @@ pub fn calc_part2((s, v): &(HashSet<char>, Vec<char>)) -> usize {
     .unwrap_or(0)
 }
 
+/// Synthesises a polymer of `size` units; about half of them react with the
+/// unit placed right before, so the reduction has work to do.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+  let mut polymer = String::with_capacity(size);
+  let mut last: Option<char> = None;
+
+  for _ in 0..size {
+    let unit = match last {
+      Some(c) if rng.chance(1, 2) => if c.is_ascii_lowercase() {
+        c.to_ascii_uppercase()
+      } else {
+        c.to_ascii_lowercase()
+      },
+      _ => {
+        let c = (b'a' + rng.below(26) as u8) as char;
+        if rng.chance(1, 2) { c.to_ascii_uppercase() } else { c }
+      },
+    };
+
+    polymer.push(unit);
+    last = Some(unit);
+  }
+
+  polymer
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+  const DAY: u8 = 5;
+
+  type Input = (HashSet<char>, Vec<char>);
+  type Output = usize;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    Ok(calc_part1(&input.1))
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    Ok(calc_part2(input))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn parse_test() {
+    let (s, v) = parse("aBbA\n").unwrap();
+
+    assert_eq!(s, ['a', 'b'].iter().cloned().collect());
+    assert_eq!(v, vec!['a', 'B', 'b', 'A']);
+    assert_eq!(parse("aB bA").unwrap_err(), ParseError::new("aB bA", 3, "unexpected unit ' '"));
+  }
+
   #[test]
   fn get_is_matching_test() {
     assert!(get_is_matching(&'a', &'A'));
@@ -73,6 +160,7 @@ mod tests {
   #[test]
   fn calc_part1_test() {
     assert_eq!(calc_part1(&String::from("dabAcCaCBAcCcaDA").chars().collect()), 10);
+    assert_eq!(calc_part1(&vec![]), 0);
   }
 
   #[test]
@@ -86,9 +174,65 @@ mod tests {
     assert_eq!(
       4,
       calc_part2(&(
-        ['a', 'b', 'c', 'd'].into_iter().cloned().collect(),
+        ['a', 'b', 'c', 'd'].iter().cloned().collect(),
         String::from("dabAcCaCBAcCcaDA").chars().collect()
       ))
     );
   }
+
+  #[test]
+  fn generate_test() {
+    assert_eq!(generate(1000, 3), generate(1000, 3));
+
+    let (_, v) = parse_str(&generate(1000, 3)).unwrap();
+    assert_eq!(v.len(), 1000);
+    assert!(calc_part1(&v) < 1000);
+  }
+}
+
+#[cfg(test)]
+mod proptests {
+  use super::*;
+  use proptest::prelude::*;
+
+  /// Reference reaction: repeatedly removes the first reacting pair.
+  fn react_naive(input: &Vec<char>) -> Vec<char> {
+    let mut v = input.clone();
+
+    while let Some(i) = (1..v.len()).find(|&i| get_is_matching(&v[i - 1], &v[i])) {
+      v.drain(i - 1..=i);
+    }
+
+    v
+  }
+
+  fn polymer() -> impl Strategy<Value = Vec<char>> {
+    prop::collection::vec(prop::sample::select(vec!['a', 'A', 'b', 'B', 'c', 'C']), 0..200)
+  }
+
+  proptest! {
+    #[test]
+    fn react_matches_naive(input in polymer()) {
+      prop_assert_eq!(react(&input), react_naive(&input));
+    }
+
+    #[test]
+    fn react_is_idempotent(input in polymer()) {
+      let reacted = react(&input);
+      prop_assert_eq!(react(&reacted), reacted);
+    }
+
+    #[test]
+    fn reacted_polymer_is_inert(input in polymer()) {
+      let reacted = react(&input);
+      prop_assert!(reacted.windows(2).all(|w| !get_is_matching(&w[0], &w[1])));
+      prop_assert_eq!(calc_part1(&input), reacted.len());
+    }
+
+    #[test]
+    fn removing_a_unit_type_never_grows_result(input in polymer()) {
+      let units: HashSet<char> = input.iter().map(|c| c.to_ascii_lowercase()).collect();
+      prop_assert!(calc_part2(&(units, input.clone())) <= calc_part1(&input));
+    }
+  }
 }
@@ -0,0 +1,47 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::Solution;
+use day6::Day6;
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// Spreads the coordinates `factor` times further apart, growing the grid
+/// area quadratically while keeping the number of coordinates.
+fn scale(factor: usize) -> String {
+  INPUT
+    .lines()
+    .map(|line| line
+      .split(", ")
+      .map(|v| (v.parse::<usize>().unwrap() * factor).to_string())
+      .collect::<Vec<String>>()
+      .join(", "))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day6");
+  group.sample_size(10);
+
+  for factor in [1, 2, 4].iter() {
+    let text = scale(*factor);
+    let input = Day6::parse_str(&text).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", factor), &text, |b, text| {
+      b.iter(|| Day6::parse_str(black_box(text)))
+    });
+    group.bench_with_input(BenchmarkId::new("part1", factor), &input, |b, input| {
+      b.iter(|| Day6::part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", factor), &input, |b, input| {
+      b.iter(|| Day6::part2(black_box(input)))
+    });
+  }
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
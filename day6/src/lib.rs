@@ -1,36 +1,33 @@
 use std::fs::File;
 use std::io::{BufReader, BufRead};
 use std::cmp;
-use std::collections::HashMap;
-
-pub fn read_input(filepath: &str) -> Result<Vec<(u64, u64)>, String> {
-  let file = File::open(filepath).map_err(|e| e.to_string())?;
-  
-  BufReader::new(file)
-    .lines()
-    .map(|line_r| parse(&line_r.map_err(|e| e.to_string())?))
-    .try_fold(vec![], |mut vec, parse_r| {
-      match parse_r {
-        Ok(t) => {
-          vec.push(t);
-          Ok(vec)
-        },
-        Err(e) => Err(e),
-      }
-    })
+use std::collections::{HashMap, HashSet};
+
+use common::{parse_lines, Cursor, ParseError, Result, Rng, Solution};
+
+pub static WATERMARK: u64 = 10000;
+
+pub fn read_from<R: BufRead>(reader: R) -> Result<Vec<(u64, u64)>> {
+  parse_lines(reader, parse)
 }
 
-fn parse(input: &String) -> Result<(u64, u64), String> {
-  input
-    .split(", ")
-    .enumerate()
-    .try_fold((0, 0), |(t0, t1), (i, s)| {
-      match (i, s.parse::<u64>()) {
-        (0, Ok(v)) => Ok((v, t1)),
-        (1, Ok(v)) => Ok((t0, v)),
-        _ => Err("input does not meet parsing criteria".to_string()),
-      }
-    })
+pub fn parse_str(input: &str) -> Result<Vec<(u64, u64)>> {
+  read_from(input.as_bytes())
+}
+
+pub fn read_input(filepath: &str) -> Result<Vec<(u64, u64)>> {
+  read_from(BufReader::new(File::open(filepath)?))
+}
+
+fn parse(input: &String) -> std::result::Result<(u64, u64), ParseError> {
+  let mut cursor = Cursor::new(input);
+
+  let x = cursor.number()?;
+  cursor.expect(", ")?;
+  let y = cursor.number()?;
+  cursor.end()?;
+
+  Ok((x, y))
 }
 
 fn calc_grid_size(input: &Vec<(u64, u64)>) -> (u64, u64) {
@@ -47,7 +44,7 @@ fn calc_closest(target: &(u64, u64), base: &Vec<(u64, u64)>) -> Option<(u64, u64
   base
     .iter()
     .map(|b| (calc_distance(b, target), *b))
-    .try_fold((true, (u64::max_value(), (0, 0))), |(is_unique, current), next| match current.0.cmp(&next.0) {
+    .try_fold((true, (u64::MAX, (0, 0))), |(is_unique, current), next| match current.0.cmp(&next.0) {
       cmp::Ordering::Equal => Some((false, current)),
       cmp::Ordering::Greater => Some((true, next)),
       cmp::Ordering::Less => Some((is_unique, current)),
@@ -72,7 +69,7 @@ fn calc_area(input: &Vec<(u64, u64)>) -> HashMap<(u64, u64), u64> {
         let entry = acc.entry(base).or_insert(0);
 
         if target.0 == 0 || target.0 == x || target.1 == 0 || target.1 == y {
-          *entry = u64::max_value();
+          *entry = u64::MAX;
         } else {
           *entry = match entry.checked_add(1) {
             Some(inc) => inc,
@@ -108,7 +105,7 @@ fn calc_safe_region(input: &Vec<(u64, u64)>, watermark: &u64) -> u64 {
 pub fn calc_part1(input: &Vec<(u64, u64)>) -> u64 {
   calc_area(input)
     .into_iter()
-    .filter(|(_, v)| *v < u64::max_value())
+    .filter(|(_, v)| *v < u64::MAX)
     .map(|(_, v)| v)
     .max_by(|v1, v2| v1.cmp(v2))
     .unwrap_or(0)
@@ -118,6 +115,46 @@ pub fn calc_part2(input: &Vec<(u64, u64)>, watermark: &u64) -> u64 {
   calc_safe_region(input, watermark)
 }
 
+/// Synthesises `size` distinct coordinates, spreading them over a grid that
+/// grows with the square root of `size` like the puzzle's does.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+  let bound = 50 * ((size as f64).sqrt() as i64 + 1);
+  let mut seen = HashSet::new();
+  let mut lines = vec![];
+
+  while lines.len() < size {
+    let coord = (rng.range(0, bound), rng.range(0, bound));
+
+    if seen.insert(coord) {
+      lines.push(format!("{}, {}", coord.0, coord.1));
+    }
+  }
+
+  lines.join("\n")
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+  const DAY: u8 = 6;
+
+  type Input = Vec<(u64, u64)>;
+  type Output = u64;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    Ok(calc_part1(input))
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    Ok(calc_part2(input, &WATERMARK))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -152,6 +189,14 @@ mod tests {
     assert!(parse(&"invalid string".to_string()).is_err());
   }
 
+  #[test]
+  fn parse_test_005() {
+    assert_eq!(parse(&"12, x".to_string()).unwrap_err().column, 5);
+    assert_eq!(parse(&"12,34".to_string()).unwrap_err().column, 3);
+    assert_eq!(parse(&"12, 34, 56".to_string()).unwrap_err().column, 7);
+    assert_eq!(parse(&"12".to_string()).unwrap_err().column, 3);
+  }
+
   #[test]
   fn calc_grid_size_test_001() {
     let input = vec![
@@ -228,16 +273,65 @@ mod tests {
   fn calc_area_test_001() {
     let input = vec![(1, 1), (6, 1), (3, 8), (4, 3), (5, 5), (9, 8)];
     let expected = vec![
-      ((1, 1), u64::max_value()),
-      ((6, 1), u64::max_value()),
-      ((3, 8), u64::max_value()),
+      ((1, 1), u64::MAX),
+      ((6, 1), u64::MAX),
+      ((3, 8), u64::MAX),
       ((4, 3), 9),
       ((5, 5), 17),
-      ((9, 8), u64::max_value()),
+      ((9, 8), u64::MAX),
     ]
       .into_iter()
       .collect::<HashMap<(u64, u64), u64>>();
 
     assert_eq!(calc_area(&input), expected);
   }
+
+  #[test]
+  fn generate_test() {
+    assert_eq!(generate(100, 3), generate(100, 3));
+
+    let input = parse_str(&generate(100, 3)).unwrap();
+    assert_eq!(input.len(), 100);
+    assert_eq!(input.iter().collect::<HashSet<_>>().len(), 100);
+  }
+}
+
+#[cfg(test)]
+mod proptests {
+  use super::*;
+  use proptest::prelude::*;
+
+  fn coord() -> impl Strategy<Value = (u64, u64)> {
+    (0..1000_u64, 0..1000_u64)
+  }
+
+  proptest! {
+    #[test]
+    fn calc_distance_is_a_metric(a in coord(), b in coord(), c in coord()) {
+      prop_assert_eq!(calc_distance(&a, &a), 0);
+      prop_assert_eq!(calc_distance(&a, &b) == 0, a == b);
+      prop_assert_eq!(calc_distance(&a, &b), calc_distance(&b, &a));
+      prop_assert!(calc_distance(&a, &c) <= calc_distance(&a, &b) + calc_distance(&b, &c));
+    }
+
+    #[test]
+    fn calc_closest_matches_oracle(target in coord(), base in prop::collection::vec(coord(), 1..20)) {
+      let best = base.iter().map(|b| calc_distance(b, &target)).min().unwrap();
+      let closest: Vec<&(u64, u64)> = base
+        .iter()
+        .filter(|b| calc_distance(b, &target) == best)
+        .collect();
+      let oracle = if closest.len() == 1 { Some(*closest[0]) } else { None };
+
+      prop_assert_eq!(calc_closest(&target, &base), oracle);
+    }
+
+    #[test]
+    fn calc_safe_region_grows_with_watermark(
+      base in prop::collection::vec((0..30_u64, 0..30_u64), 1..8),
+      watermark in 0..200_u64,
+    ) {
+      prop_assert!(calc_safe_region(&base, &watermark) <= calc_safe_region(&base, &(watermark + 10)));
+    }
+  }
 }
@@ -1,13 +1,18 @@
-use day6::read_input;
+use std::env;
+
+use common::{Result, Source};
+
+use day6::read_from;
 use day6::calc_part1;
 use day6::calc_part2;
+use day6::WATERMARK;
 
-static WATERMARK: u64 = 10000;
-
-fn main() {
-  let filepath = "./input.txt";
-  let input = read_input(filepath).unwrap();
+fn main() -> Result<()> {
+  let source = Source::from_arg(&env::args().nth(1).unwrap_or_else(|| "./input.txt".to_string()));
+  let input = read_from(source.reader()?)?;
 
   println!("Day #6, part #1 {:?}", calc_part1(&input));
   println!("Day #6, part #2 {:?}", calc_part2(&input, &WATERMARK));
+
+  Ok(())
 }
\ No newline at end of file
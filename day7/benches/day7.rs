@@ -0,0 +1,24 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use common::Solution;
+use day7::Day7;
+
+static INPUT: &str = include_str!("../input.txt");
+
+/// Step names are single letters, so the instructions cannot grow beyond
+/// the puzzle input; only the real input is measured.
+fn bench(c: &mut Criterion) {
+  let mut group = c.benchmark_group("day7");
+  let input = Day7::parse_str(INPUT).unwrap();
+
+  group.bench_function("parse", |b| b.iter(|| Day7::parse_str(black_box(INPUT))));
+  group.bench_function("part1", |b| b.iter(|| Day7::part1(black_box(&input))));
+  group.bench_function("part2", |b| b.iter(|| Day7::part2(black_box(&input))));
+
+  group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
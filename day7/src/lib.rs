@@ -1,42 +1,159 @@
-#[macro_use] extern crate lazy_static;
-
 use std::fs;
 use std::io::{self, BufRead};
+use std::collections::{BTreeMap, BTreeSet};
 
-use regex::Regex;
+use common::{parse_lines, Cursor, Error, ParseError, Result, Rng, Solution};
 
-pub fn read_input(filepath: &str) -> Result<Vec<(char, char)>, String> {
-  let file = fs::File::open(filepath)
-    .map_err(|e| e.to_string())?;
+pub static WORKERS: usize = 5;
+pub static BASE_DURATION: u32 = 60;
 
-  io::BufReader::new(file)
-    .lines()
-    .map(|line_r| parse(&line_r.map_err(|e| e.to_string())?))
-    .collect::<Result<Vec<(char, char)>, _>>()
+pub fn read_from<R: BufRead>(reader: R) -> Result<Vec<(char, char)>> {
+  parse_lines(reader, parse)
 }
 
-fn parse(input: &String) -> Result<(char, char), String> {
-  lazy_static! {
-    static ref CHARS_RE: Regex = Regex::new(r"Step ([A-Z]{1}) must be finished before step ([A-Z]{1}) can begin.").unwrap();
-  }
+pub fn parse_str(input: &str) -> Result<Vec<(char, char)>> {
+  read_from(input.as_bytes())
+}
 
-  let captures = CHARS_RE
-    .captures(input)
-    .ok_or("could not parse line")?;
+pub fn read_input(filepath: &str) -> Result<Vec<(char, char)>> {
+  read_from(io::BufReader::new(fs::File::open(filepath)?))
+}
 
-  let mut chr_iter = captures
-    .iter()
-    .skip(1)
-    .take(2)
-    .map(|o| o.unwrap())
-    .map(|m| m.as_str().as_bytes()[0] as char);
+fn parse(input: &String) -> std::result::Result<(char, char), ParseError> {
+  let mut cursor = Cursor::new(input);
 
-  let left = chr_iter.next().ok_or("error unpacking left character")?;
-  let right = chr_iter.next().ok_or("error unpacking right character")?;
+  cursor.expect("Step ")?;
+  let left = cursor.char_if("step letter", |c| c.is_ascii_uppercase())?;
+  cursor.expect(" must be finished before step ")?;
+  let right = cursor.char_if("step letter", |c| c.is_ascii_uppercase())?;
+  cursor.expect(" can begin.")?;
+  cursor.end()?;
 
   Ok((left, right))
 }
 
+fn build_dependencies(input: &Vec<(char, char)>) -> BTreeMap<char, BTreeSet<char>> {
+  input
+    .iter()
+    .fold(BTreeMap::new(), |mut map, (before, after)| {
+      map.entry(*before).or_insert_with(BTreeSet::new);
+      map.entry(*after).or_insert_with(BTreeSet::new).insert(*before);
+      map
+    })
+}
+
+fn find_available(
+  dependencies: &BTreeMap<char, BTreeSet<char>>,
+  done: &BTreeSet<char>,
+  taken: &BTreeSet<char>,
+) -> Option<char> {
+  dependencies
+    .iter()
+    .filter(|(step, _)| !done.contains(step) && !taken.contains(step))
+    .find(|(_, before)| before.is_subset(done))
+    .map(|(step, _)| *step)
+}
+
+fn calc_duration(step: char, base_duration: &u32) -> u32 {
+  base_duration + (step as u32 - 'A' as u32 + 1)
+}
+
+/// The steps left over once nothing more can start wait on each other.
+fn check_finished(dependencies: &BTreeMap<char, BTreeSet<char>>, done: &BTreeSet<char>) -> Result<()> {
+  if done.len() < dependencies.len() {
+    let stuck: String = dependencies.keys().filter(|step| !done.contains(step)).collect();
+    return Err(Error::NoSolution(format!("steps {} depend on each other in a cycle", stuck)));
+  }
+
+  Ok(())
+}
+
+pub fn calc_part1(input: &Vec<(char, char)>) -> Result<String> {
+  let dependencies = build_dependencies(input);
+  let mut done = BTreeSet::new();
+  let mut order = String::new();
+
+  while let Some(step) = find_available(&dependencies, &done, &BTreeSet::new()) {
+    done.insert(step);
+    order.push(step);
+  }
+
+  check_finished(&dependencies, &done)?;
+  Ok(order)
+}
+
+pub fn calc_part2(input: &Vec<(char, char)>, workers: &usize, base_duration: &u32) -> Result<u32> {
+  let dependencies = build_dependencies(input);
+  let mut done = BTreeSet::new();
+  let mut in_progress: BTreeMap<char, u32> = BTreeMap::new();
+  let mut time = 0;
+
+  loop {
+    while in_progress.len() < *workers {
+      let taken = in_progress.keys().cloned().collect();
+
+      match find_available(&dependencies, &done, &taken) {
+        Some(step) => in_progress.insert(step, time + calc_duration(step, base_duration)),
+        None => break,
+      };
+    }
+
+    let finish = match in_progress.values().min() {
+      Some(finish) => *finish,
+      None => return check_finished(&dependencies, &done).map(|_| time),
+    };
+
+    time = finish;
+    in_progress.retain(|step, t| if *t == finish {
+      done.insert(*step);
+      false
+    } else {
+      true
+    });
+  }
+}
+
+/// Synthesises up to `size` acyclic dependencies between steps `A` to `Z`.
+/// Only 325 distinct pairs exist, so larger sizes are capped.
+pub fn generate(size: usize, seed: u64) -> String {
+  let mut rng = Rng::new(seed);
+  let mut order: Vec<char> = ('A'..='Z').collect();
+  rng.shuffle(&mut order);
+
+  let mut pairs: Vec<(usize, usize)> = (0..order.len())
+    .flat_map(|i| (i + 1..order.len()).map(move |j| (i, j)))
+    .collect();
+  rng.shuffle(&mut pairs);
+
+  pairs
+    .into_iter()
+    .take(size)
+    .map(|(i, j)| format!("Step {} must be finished before step {} can begin.", order[i], order[j]))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+  const DAY: u8 = 7;
+
+  type Input = Vec<(char, char)>;
+  type Output = String;
+
+  fn parse<R: BufRead>(reader: R) -> Result<Self::Input> {
+    read_from(reader)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output> {
+    calc_part1(input)
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output> {
+    calc_part2(input, &WORKERS, &BASE_DURATION).map(|time| time.to_string())
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -53,7 +170,7 @@ mod tests {
   #[test]
   fn parse_test_002() {
     let input = "Step T must be finished before step _ can begin.";
-    let expected = Err("could not parse line".to_string());
+    let expected = Err(ParseError::new(input, 37, "expected step letter, found '_'"));
     let actual = parse(&input.to_string());
 
     assert_eq!(expected, actual);
@@ -62,7 +179,7 @@ mod tests {
   #[test]
   fn parse_test_003() {
     let input = "Step T must be finished before step 🔥 can begin.";
-    let expected = Err("could not parse line".to_string());
+    let expected = Err(ParseError::new(input, 37, "expected step letter, found '🔥'"));
     let actual = parse(&input.to_string());
 
     assert_eq!(expected, actual);
@@ -71,9 +188,68 @@ mod tests {
   #[test]
   fn parse_test_004() {
     let input = "Random content.";
-    let expected = Err("could not parse line".to_string());
+    let expected = Err(ParseError::new(input, 1, "expected \"Step \", found 'R'"));
     let actual = parse(&input.to_string());
 
     assert_eq!(expected, actual);
   }
+
+  fn example() -> Vec<(char, char)> {
+    vec![
+      ('C', 'A'),
+      ('C', 'F'),
+      ('A', 'B'),
+      ('A', 'D'),
+      ('B', 'E'),
+      ('D', 'E'),
+      ('F', 'E'),
+    ]
+  }
+
+  #[test]
+  fn parse_str_test() {
+    let input = "Step C must be finished before step A can begin.\n\
+      Step C must be finished before step F can begin.\n";
+
+    assert_eq!(parse_str(input).unwrap(), vec![('C', 'A'), ('C', 'F')]);
+  }
+
+  #[test]
+  fn calc_part1_test() {
+    assert_eq!(calc_part1(&example()).unwrap(), "CABDFE".to_string());
+  }
+
+  #[test]
+  fn calc_part2_test() {
+    assert_eq!(calc_part2(&example(), &2, &0).unwrap(), 15);
+  }
+
+  #[test]
+  fn cycle_test() {
+    let mut input = example();
+    input.push(('E', 'C'));
+
+    assert!(matches!(calc_part1(&input), Err(Error::NoSolution(_))));
+    assert!(matches!(calc_part2(&input, &2, &0), Err(Error::NoSolution(_))));
+    assert!(matches!(calc_part2(&example(), &0, &0), Err(Error::NoSolution(_))));
+  }
+
+  #[test]
+  fn generate_test() {
+    assert_eq!(generate(50, 3), generate(50, 3));
+    assert_eq!(parse_str(&generate(50, 3)).unwrap().len(), 50);
+    assert_eq!(parse_str(&generate(1000, 3)).unwrap().len(), 325);
+    assert_eq!(calc_part1(&parse_str(&generate(1000, 3)).unwrap()).unwrap().len(), 26);
+  }
+
+  #[test]
+  fn generate_read_input_test() {
+    let path = std::env::temp_dir().join(format!("day7-generate-{}.txt", std::process::id()));
+    fs::write(&path, generate(50, 3)).unwrap();
+
+    let input = read_input(path.to_str().unwrap());
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(input.unwrap(), parse_str(&generate(50, 3)).unwrap());
+  }
 }
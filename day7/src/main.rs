@@ -1,8 +1,18 @@
-use day7::read_input;
-
-fn main() {
-  let filepath = "./input.txt";
-  let tuple_v = read_input(&filepath);
-
-  println!("{:?}", tuple_v);
+use std::env;
+
+use common::{Result, Source};
+
+use day7::read_from;
+use day7::calc_part1;
+use day7::calc_part2;
+use day7::{WORKERS, BASE_DURATION};
+
+fn main() -> Result<()> {
+  let source = Source::from_arg(&env::args().nth(1).unwrap_or_else(|| "./input.txt".to_string()));
+  let input = read_from(source.reader()?)?;
+
+  println!("Day #7, part #1 {}", calc_part1(&input)?);
+  println!("Day #7, part #2 {}", calc_part2(&input, &WORKERS, &BASE_DURATION)?);
+
+  Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  aoc_fuzz::check("day1", data, |r| day1::read_from(r), day1::read_input);
+});
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  aoc_fuzz::check("day3", data, |r| day3::read_from(r), day3::read_input);
+});
@@ -0,0 +1,25 @@
+//! Shared driver for the fuzz targets, one per day. Run them with
+//! `cargo +nightly fuzz run day4` from this directory.
+
+use std::fs;
+use std::process;
+
+use common::Result;
+
+/// Feeds `data` to a day's parser both from memory and through a file on
+/// disk. Any panic along the way is reported by libFuzzer as a crash; the
+/// two entry points must also agree on whether the input is valid.
+pub fn check<T>(
+  day: &str,
+  data: &[u8],
+  read_from: fn(&[u8]) -> Result<T>,
+  read_input: fn(&str) -> Result<T>,
+) {
+  let from_reader = read_from(data);
+
+  let path = std::env::temp_dir().join(format!("aoc-fuzz-{}-{}", day, process::id()));
+  fs::write(&path, data).unwrap();
+  let from_file = read_input(path.to_str().unwrap());
+
+  assert_eq!(from_reader.is_ok(), from_file.is_ok());
+}